@@ -0,0 +1,667 @@
+// src/cli.rs
+use crate::hotkey::parse_hotkey;
+use crate::input_sim::InputSpeed;
+use crate::source::TextSource;
+use regex::Regex;
+use std::path::PathBuf;
+
+// 退出码
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+
+// 只对实际输入起作用的 type 选项，analyze 不接受
+const TYPE_ONLY_FLAGS: &[&str] = &[
+    "--countdown",
+    "--target-title",
+    "--target-exe",
+    "--wait",
+    "--click",
+    "--target-timeout",
+    "--background",
+    "--raw",
+    "--verify",
+];
+
+pub const USAGE: &str = "\
+用法 (Usage):
+  paste_bypass [--config <路径>] <子命令> [选项]
+  paste_bypass                      交互式菜单 (interactive menu)
+  paste_bypass type [选项]          立即模拟输入 (type once and exit)
+      --from-clipboard              从剪贴板读取（默认） (read the clipboard, default)
+      --file <路径>                 从文件读取，自动识别UTF-8/UTF-16/GBK (read a file, UTF-8/UTF-16/GBK detected)
+      --stdin                       从标准输入读取 (read standard input)
+      --text <文本>                 输入参数中的文本 (type the given text)
+      --history <N>                 第N新的剪贴板历史条目 (the Nth most recent history entry)
+      --snippet <名称>              片段库中的片段 (a snippet from the library)
+      --profile <名称>              使用配置方案 (use a named profile)
+      --speed <slow|normal|fast|turbo>  内置方案的别名 (alias for the built-in profiles)
+      --delay <毫秒>                字符间延迟 (per-character delay in ms)
+      --countdown <秒>              开始前倒计时 (countdown before typing)
+      --target-title <正则>         激活标题匹配的窗口后输入，代替倒计时 (activate the window whose title matches, instead of the countdown)
+      --target-exe <程序名>         激活该程序的窗口后输入，例如 putty.exe (activate a window of this program, e.g. putty.exe)
+      --wait                        等待目标窗口成为前台窗口，不主动激活 (wait for the target to become foreground instead of activating it)
+      --click                       点击要输入的位置后开始，右键取消 (start after you click the field, right-click cancels)
+      --target-timeout <秒>         目标窗口的超时，默认10 (timeout for the target window, default 10)
+      --background                  向目标控件发送 WM_CHAR 消息，不切换前台窗口 (post WM_CHAR messages to the target control without bringing it to the front)
+      --secret                      敏感内容，不记录历史 (sensitive text, kept out of history)
+      --raw                         不展开模板变量 (do not expand template variables)
+      --verify                      输入后全选复制输入框内容进行校验 (select and copy the field afterwards to verify it)
+  paste_bypass analyze [选项]       预检分析，不输入 (pre-flight analysis without typing)
+      来源选项、--profile、--speed、--delay 和 --secret 同 type (source options, --profile, --speed, --delay and --secret as for type)
+  paste_bypass daemon [选项]        仅监听热键，无菜单 (hotkeys only, no menu)
+      --hotkey <组合键>             例如 Ctrl+Shift+V (e.g. Ctrl+Shift+V)
+      --profile <名称> / --speed <slow|normal|fast|turbo>
+      --countdown <秒>
+      --target-title <正则> / --target-exe <程序名> / --wait / --click / --target-timeout <秒> / --background
+      --watch                       剪贴板变化时自动捕获 (capture automatically on clipboard change)
+      --secret                      敏感内容模式 (secret mode)
+  paste_bypass snippet list [关键词]     列出或按名称、标签、内容搜索片段 (list snippets, or search names, tags and text)
+  paste_bypass snippet add <名称> [选项]  保存片段，来源选项同 type，默认剪贴板 (save a snippet; source options as for type, clipboard by default)
+      --tag <标签>                  可重复 (repeatable)
+      --slot <N>                    绑定到前导键 + N (bind to leader key + N)
+  paste_bypass snippet remove <名称>    删除片段 (delete a snippet)
+  paste_bypass vault init           创建加密密码库 (create the encrypted vault)
+  paste_bypass vault list           列出条目和字段名 (list entries and field names)
+  paste_bypass vault add <名称> [选项]  添加条目，字段值在控制台输入 (add an entry; values are entered in the console)
+      --field <字段>                额外的隐藏字段，可重复 (extra hidden field, repeatable)
+      --sequence <序列>             自动输入序列 (auto-type sequence)
+  paste_bypass vault remove <名称>  删除条目 (delete an entry)
+  paste_bypass vault type <名称> [选项]  自动输入条目 (auto-type an entry)
+      --sequence <序列>             默认 {USERNAME}{TAB}{PASSWORD}{ENTER} (default {USERNAME}{TAB}{PASSWORD}{ENTER})
+      --profile <名称> / --speed <slow|normal|fast|turbo>
+      --countdown <秒>
+      --target-title <正则> / --target-exe <程序名> / --wait / --click / --target-timeout <秒> / --background
+  paste_bypass history               列出剪贴板历史，需开启 history.persist (list clipboard history, needs history.persist)
+  paste_bypass config path          显示配置文件位置 (print the config file location)
+  paste_bypass config init          写入配置文件模板 (write a config template)
+  paste_bypass help                 显示本帮助 (show this help)
+
+退出码 (Exit codes): 0 成功 (success), 1 执行失败、预检或校验未通过 (failure, blocked by pre-flight checks, or verification mismatch), 2 参数错误 (usage error)";
+
+pub struct Invocation {
+    pub config_path: Option<PathBuf>,
+    pub command: Command,
+}
+
+pub enum Command {
+    Interactive,
+    Type(TypeOptions),
+    Analyze(TypeOptions),
+    Daemon(DaemonOptions),
+    Config(ConfigAction),
+    Snippet(SnippetAction),
+    Vault(VaultAction),
+    History,
+    Help,
+}
+
+pub enum ConfigAction {
+    Path,
+    Init,
+}
+
+pub enum SnippetAction {
+    List(Option<String>), // 可选的搜索关键词
+    Add {
+        name: String,
+        tags: Vec<String>,
+        slot: Option<usize>,
+        source: TextSource,
+    },
+    Remove(String),
+}
+
+pub enum VaultAction {
+    Init,
+    List,
+    Add {
+        name: String,
+        fields: Vec<String>, // 额外的字段名
+        sequence: Option<String>,
+    },
+    Remove(String),
+    Type {
+        name: String,
+        sequence: Option<String>,
+        profile: Option<String>,
+        countdown: Option<u64>,
+        target: TargetOptions,
+    },
+}
+
+// 命令行指定的目标窗口，覆盖配置文件中的 [target]
+#[derive(Default)]
+pub struct TargetOptions {
+    pub title: Option<Regex>,
+    pub exe: Option<String>,
+    pub wait: bool,
+    pub click: bool,
+    pub background: bool,
+    pub timeout_secs: Option<u64>,
+}
+
+pub struct TypeOptions {
+    pub source: TextSource,
+    pub profile: Option<String>,
+    pub delay_ms: Option<u64>,
+    pub countdown: Option<u64>,
+    pub target: TargetOptions,
+    pub secret: bool,
+    pub raw: bool,    // 不展开模板变量
+    pub verify: bool, // 输入后读回输入框内容校验
+}
+
+pub struct DaemonOptions {
+    pub hotkey: Option<String>,
+    pub profile: Option<String>,
+    pub countdown: Option<u64>,
+    pub target: TargetOptions,
+    pub watch: bool,
+    pub secret: bool,
+}
+
+// 解析命令行参数（不含程序名）
+pub fn parse<I>(args: I) -> Result<Invocation, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter().peekable();
+    let mut config_path = None;
+
+    // 全局选项位于子命令之前
+    while args.peek().map(String::as_str) == Some("--config") {
+        let flag = args.next().unwrap_or_default();
+        config_path = Some(PathBuf::from(value(&flag, &mut args)?));
+    }
+
+    let command = match args.next() {
+        None => Command::Interactive,
+        Some(subcommand) => match subcommand.as_str() {
+            "type" => Command::Type(parse_type(&subcommand, args)?),
+            "analyze" => Command::Analyze(parse_type(&subcommand, args)?),
+            "daemon" => Command::Daemon(parse_daemon(args)?),
+            "config" => Command::Config(parse_config(args)?),
+            "snippet" => Command::Snippet(parse_snippet(args)?),
+            "vault" => Command::Vault(parse_vault(args)?),
+            "history" => {
+                if let Some(extra) = args.next() {
+                    return Err(format!("history 不支持的参数: {}", extra));
+                }
+                Command::History
+            }
+            "interactive" => Command::Interactive,
+            "help" | "--help" | "-h" => Command::Help,
+            other => return Err(format!("未知的子命令: {}", other)),
+        },
+    };
+
+    Ok(Invocation {
+        config_path,
+        command,
+    })
+}
+
+// type 和 analyze 共用的选项
+fn parse_type<I>(subcommand: &str, mut args: I) -> Result<TypeOptions, String>
+where
+    I: Iterator<Item = String>,
+{
+    let mut options = TypeOptions {
+        source: TextSource::Clipboard,
+        profile: None,
+        delay_ms: None,
+        countdown: None,
+        target: TargetOptions::default(),
+        secret: false,
+        raw: false,
+        verify: false,
+    };
+
+    while let Some(arg) = args.next() {
+        if subcommand == "analyze" && TYPE_ONLY_FLAGS.contains(&arg.as_str()) {
+            return Err(format!("analyze 不支持的参数: {}", arg));
+        }
+        if parse_target(&arg, &mut args, &mut options.target)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--from-clipboard" => options.source = TextSource::Clipboard,
+            "--file" => options.source = TextSource::File(value(&arg, &mut args)?.into()),
+            "--stdin" => options.source = TextSource::Stdin,
+            "--text" => options.source = TextSource::Literal(value(&arg, &mut args)?),
+            "--history" => options.source = TextSource::History(number(&arg, &mut args)?),
+            "--snippet" => options.source = TextSource::Snippet(value(&arg, &mut args)?),
+            "--profile" => options.profile = Some(value(&arg, &mut args)?),
+            "--speed" => options.profile = Some(speed(&arg, &mut args)?),
+            "--delay" => options.delay_ms = Some(number(&arg, &mut args)?),
+            "--countdown" => options.countdown = Some(number(&arg, &mut args)?),
+            "--secret" => options.secret = true,
+            "--raw" => options.raw = true,
+            "--verify" => options.verify = true,
+            other => return Err(format!("{} 不支持的参数: {}", subcommand, other)),
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_daemon<I>(mut args: I) -> Result<DaemonOptions, String>
+where
+    I: Iterator<Item = String>,
+{
+    let mut options = DaemonOptions {
+        hotkey: None,
+        profile: None,
+        countdown: None,
+        target: TargetOptions::default(),
+        watch: false,
+        secret: false,
+    };
+
+    while let Some(arg) = args.next() {
+        if parse_target(&arg, &mut args, &mut options.target)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--hotkey" => {
+                let hotkey = value(&arg, &mut args)?;
+                parse_hotkey(&hotkey)?;
+                options.hotkey = Some(hotkey);
+            }
+            "--profile" => options.profile = Some(value(&arg, &mut args)?),
+            "--speed" => options.profile = Some(speed(&arg, &mut args)?),
+            "--countdown" => options.countdown = Some(number(&arg, &mut args)?),
+            "--watch" => options.watch = true,
+            "--secret" => options.secret = true,
+            other => return Err(format!("daemon 不支持的参数: {}", other)),
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_snippet<I>(mut args: I) -> Result<SnippetAction, String>
+where
+    I: Iterator<Item = String>,
+{
+    let action = match args.next().as_deref() {
+        Some("list") | Some("search") => SnippetAction::List(args.next()),
+        Some("remove") => SnippetAction::Remove(args.next().ok_or("snippet remove 需要片段名称")?),
+        Some("add") => {
+            let name = args.next().ok_or("snippet add 需要片段名称")?;
+            let mut tags = Vec::new();
+            let mut slot = None;
+            let mut source = TextSource::Clipboard;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--tag" => tags.push(value(&arg, &mut args)?),
+                    "--slot" => {
+                        let n = number(&arg, &mut args)?;
+                        if !(1..=9).contains(&n) {
+                            return Err(format!("--slot 需要 1-9，得到: {}", n));
+                        }
+                        slot = Some(n);
+                    }
+                    "--from-clipboard" => source = TextSource::Clipboard,
+                    "--file" => source = TextSource::File(value(&arg, &mut args)?.into()),
+                    "--stdin" => source = TextSource::Stdin,
+                    "--text" => source = TextSource::Literal(value(&arg, &mut args)?),
+                    "--history" => source = TextSource::History(number(&arg, &mut args)?),
+                    other => return Err(format!("snippet add 不支持的参数: {}", other)),
+                }
+            }
+            return Ok(SnippetAction::Add {
+                name,
+                tags,
+                slot,
+                source,
+            });
+        }
+        Some(other) => return Err(format!("snippet 不支持的操作: {}", other)),
+        None => return Err("snippet 需要一个操作 (list/search/add/remove)".into()),
+    };
+
+    if let Some(extra) = args.next() {
+        return Err(format!("snippet 不支持的参数: {}", extra));
+    }
+    Ok(action)
+}
+
+fn parse_vault<I>(mut args: I) -> Result<VaultAction, String>
+where
+    I: Iterator<Item = String>,
+{
+    let action = match args.next().as_deref() {
+        Some("init") => VaultAction::Init,
+        Some("list") => VaultAction::List,
+        Some("remove") => VaultAction::Remove(args.next().ok_or("vault remove 需要条目名称")?),
+        Some("add") => {
+            let name = args.next().ok_or("vault add 需要条目名称")?;
+            let mut fields = Vec::new();
+            let mut sequence = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--field" => fields.push(value(&arg, &mut args)?),
+                    "--sequence" => sequence = Some(value(&arg, &mut args)?),
+                    other => return Err(format!("vault add 不支持的参数: {}", other)),
+                }
+            }
+            return Ok(VaultAction::Add {
+                name,
+                fields,
+                sequence,
+            });
+        }
+        Some("type") => {
+            let name = args.next().ok_or("vault type 需要条目名称")?;
+            let mut sequence = None;
+            let mut profile = None;
+            let mut countdown = None;
+            let mut target = TargetOptions::default();
+            while let Some(arg) = args.next() {
+                if parse_target(&arg, &mut args, &mut target)? {
+                    continue;
+                }
+                match arg.as_str() {
+                    "--sequence" => sequence = Some(value(&arg, &mut args)?),
+                    "--profile" => profile = Some(value(&arg, &mut args)?),
+                    "--speed" => profile = Some(speed(&arg, &mut args)?),
+                    "--countdown" => countdown = Some(number(&arg, &mut args)?),
+                    other => return Err(format!("vault type 不支持的参数: {}", other)),
+                }
+            }
+            return Ok(VaultAction::Type {
+                name,
+                sequence,
+                profile,
+                countdown,
+                target,
+            });
+        }
+        Some(other) => return Err(format!("vault 不支持的操作: {}", other)),
+        None => return Err("vault 需要一个操作 (init/list/add/remove/type)".into()),
+    };
+
+    if let Some(extra) = args.next() {
+        return Err(format!("vault 不支持的参数: {}", extra));
+    }
+    Ok(action)
+}
+
+fn parse_config<I>(mut args: I) -> Result<ConfigAction, String>
+where
+    I: Iterator<Item = String>,
+{
+    let action = match args.next().as_deref() {
+        Some("path") => ConfigAction::Path,
+        Some("init") => ConfigAction::Init,
+        Some(other) => return Err(format!("config 不支持的操作: {}", other)),
+        None => return Err("config 需要一个操作 (path/init)".into()),
+    };
+
+    if let Some(extra) = args.next() {
+        return Err(format!("config 不支持的参数: {}", extra));
+    }
+    Ok(action)
+}
+
+// 解析目标窗口选项，返回参数是否属于目标窗口选项
+fn parse_target<I>(arg: &str, args: &mut I, target: &mut TargetOptions) -> Result<bool, String>
+where
+    I: Iterator<Item = String>,
+{
+    match arg {
+        "--target-title" => {
+            let pattern = value(arg, args)?;
+            let title =
+                Regex::new(&pattern).map_err(|e| format!("{} 不是有效的正则表达式: {}", arg, e))?;
+            target.title = Some(title);
+        }
+        "--target-exe" => target.exe = Some(value(arg, args)?),
+        "--wait" => target.wait = true,
+        "--click" => target.click = true,
+        "--background" => target.background = true,
+        "--target-timeout" => target.timeout_secs = Some(number(arg, args)?),
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+// 读取选项的值
+fn value<I>(flag: &str, args: &mut I) -> Result<String, String>
+where
+    I: Iterator<Item = String>,
+{
+    args.next().ok_or_else(|| format!("{} 需要一个值", flag))
+}
+
+// --speed 只接受内置方案名称
+fn speed<I>(flag: &str, args: &mut I) -> Result<String, String>
+where
+    I: Iterator<Item = String>,
+{
+    let speed: InputSpeed = value(flag, args)?.parse()?;
+    Ok(speed.name().into())
+}
+
+fn number<I, T>(flag: &str, args: &mut I) -> Result<T, String>
+where
+    I: Iterator<Item = String>,
+    T: std::str::FromStr,
+{
+    let raw = value(flag, args)?;
+    raw.parse()
+        .map_err(|_| format!("{} 需要一个数字，得到: {}", flag, raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(args: &[&str]) -> Result<Invocation, String> {
+        parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn command(args: &[&str]) -> Command {
+        match run(args) {
+            Ok(invocation) => invocation.command,
+            Err(e) => panic!("{:?}: {}", args, e),
+        }
+    }
+
+    fn error(args: &[&str]) -> String {
+        match run(args) {
+            Ok(_) => panic!("{:?} 应当解析失败", args),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn no_arguments_opens_the_menu() {
+        assert!(matches!(command(&[]), Command::Interactive));
+        assert!(matches!(command(&["help"]), Command::Help));
+        assert!(matches!(command(&["-h"]), Command::Help));
+    }
+
+    #[test]
+    fn global_config_option() {
+        let invocation = run(&["--config", "a.toml", "history"]).unwrap();
+        assert_eq!(invocation.config_path, Some(PathBuf::from("a.toml")));
+        assert!(matches!(invocation.command, Command::History));
+        assert!(error(&["--config"]).contains("--config"));
+    }
+
+    #[test]
+    fn type_options() {
+        let Command::Type(options) = command(&[
+            "type",
+            "--file",
+            "notes.txt",
+            "--speed",
+            "FAST",
+            "--delay",
+            "20",
+            "--countdown",
+            "0",
+            "--target-exe",
+            "putty.exe",
+            "--wait",
+            "--raw",
+            "--verify",
+            "--secret",
+        ]) else {
+            panic!("应为 type");
+        };
+        assert!(
+            matches!(&options.source, TextSource::File(path) if path == &PathBuf::from("notes.txt"))
+        );
+        assert_eq!(options.profile.as_deref(), Some("fast"));
+        assert_eq!((options.delay_ms, options.countdown), (Some(20), Some(0)));
+        assert_eq!(options.target.exe.as_deref(), Some("putty.exe"));
+        assert!(options.target.wait && options.raw && options.verify && options.secret);
+    }
+
+    #[test]
+    fn type_defaults_to_clipboard_and_last_source_wins() {
+        let Command::Type(options) = command(&["type"]) else {
+            panic!("应为 type");
+        };
+        assert!(matches!(options.source, TextSource::Clipboard));
+
+        let Command::Type(options) = command(&["type", "--stdin", "--history", "2"]) else {
+            panic!("应为 type");
+        };
+        assert!(matches!(options.source, TextSource::History(2)));
+    }
+
+    #[test]
+    fn missing_and_invalid_values() {
+        assert!(error(&["type", "--file"]).contains("--file"));
+        assert!(error(&["type", "--delay", "fast"]).contains("--delay"));
+        assert!(error(&["type", "--speed", "warp"]).contains("warp"));
+        assert!(error(&["type", "--target-title", "("]).contains("--target-title"));
+        assert!(error(&["daemon", "--hotkey", "Ctrl+Nope"]).contains("Nope"));
+        assert!(error(&["snippet", "add", "x", "--slot", "10"]).contains("--slot"));
+    }
+
+    #[test]
+    fn unknown_flags_and_subcommands() {
+        assert!(error(&["type", "--bogus"]).contains("--bogus"));
+        assert!(error(&["daemon", "--verify"]).contains("--verify"));
+        assert!(error(&["frobnicate"]).contains("frobnicate"));
+        assert!(error(&["history", "extra"]).contains("extra"));
+        assert!(error(&["config"]).contains("config"));
+        assert!(error(&["config", "path", "extra"]).contains("extra"));
+        assert!(error(&["snippet", "rename"]).contains("rename"));
+        assert!(error(&["vault"]).contains("vault"));
+    }
+
+    #[test]
+    fn analyze_rejects_typing_options() {
+        let Command::Analyze(options) = command(&[
+            "analyze",
+            "--text",
+            "abc",
+            "--profile",
+            "console",
+            "--delay",
+            "5",
+        ]) else {
+            panic!("应为 analyze");
+        };
+        assert!(matches!(&options.source, TextSource::Literal(text) if text == "abc"));
+        assert_eq!(options.profile.as_deref(), Some("console"));
+
+        for flag in ["--verify", "--raw", "--wait", "--background", "--click"] {
+            assert!(error(&["analyze", flag]).contains(flag));
+        }
+        assert!(error(&["analyze", "--countdown", "3"]).contains("--countdown"));
+        assert!(error(&["analyze", "--target-exe", "putty.exe"]).contains("--target-exe"));
+    }
+
+    #[test]
+    fn daemon_options() {
+        let Command::Daemon(options) = command(&[
+            "daemon",
+            "--hotkey",
+            "Ctrl+Alt+P",
+            "--watch",
+            "--background",
+        ]) else {
+            panic!("应为 daemon");
+        };
+        assert_eq!(options.hotkey.as_deref(), Some("Ctrl+Alt+P"));
+        assert!(options.watch && options.target.background && !options.secret);
+    }
+
+    #[test]
+    fn snippet_actions() {
+        let Command::Snippet(SnippetAction::Add {
+            name,
+            tags,
+            slot,
+            source,
+        }) = command(&[
+            "snippet", "add", "sig", "--tag", "a", "--tag", "b", "--slot", "3", "--stdin",
+        ])
+        else {
+            panic!("应为 snippet add");
+        };
+        assert_eq!(
+            (name.as_str(), tags, slot),
+            ("sig", vec!["a".to_string(), "b".to_string()], Some(3))
+        );
+        assert!(matches!(source, TextSource::Stdin));
+
+        assert!(
+            matches!(command(&["snippet", "search", "ssh"]), Command::Snippet(SnippetAction::List(Some(q))) if q == "ssh")
+        );
+        assert!(
+            matches!(command(&["snippet", "remove", "sig"]), Command::Snippet(SnippetAction::Remove(n)) if n == "sig")
+        );
+        assert!(error(&["snippet", "remove"]).contains("snippet remove"));
+    }
+
+    #[test]
+    fn vault_actions() {
+        assert!(matches!(
+            command(&["vault", "init"]),
+            Command::Vault(VaultAction::Init)
+        ));
+        let Command::Vault(VaultAction::Add {
+            name,
+            fields,
+            sequence,
+        }) = command(&[
+            "vault",
+            "add",
+            "mail",
+            "--field",
+            "otp",
+            "--sequence",
+            "{PASSWORD}",
+        ])
+        else {
+            panic!("应为 vault add");
+        };
+        assert_eq!(
+            (name.as_str(), fields, sequence.as_deref()),
+            ("mail", vec!["otp".to_string()], Some("{PASSWORD}"))
+        );
+
+        let Command::Vault(VaultAction::Type {
+            name,
+            countdown,
+            target,
+            ..
+        }) = command(&["vault", "type", "mail", "--countdown", "2", "--click"])
+        else {
+            panic!("应为 vault type");
+        };
+        assert_eq!((name.as_str(), countdown), ("mail", Some(2)));
+        assert!(target.click);
+        assert!(error(&["vault", "type"]).contains("vault type"));
+        assert!(error(&["vault", "add", "mail", "--verify"]).contains("--verify"));
+    }
+}
@@ -0,0 +1,97 @@
+// src/click.rs
+// 点击选择目标：用低级鼠标钩子等待用户的下一次点击
+use std::cell::Cell;
+use std::ptr::null_mut;
+use std::time::Duration;
+use winapi::ctypes::c_int;
+use winapi::shared::minwindef::{LPARAM, LRESULT, UINT, WPARAM};
+use winapi::shared::windef::HWND;
+use winapi::um::libloaderapi::GetModuleHandleW;
+use winapi::um::wincon::GetConsoleWindow;
+use winapi::um::winuser::{
+    CallNextHookEx, DispatchMessageW, GetAncestor, GetMessageW, KillTimer, PostQuitMessage,
+    SetTimer, SetWindowsHookExW, TranslateMessage, UnhookWindowsHookEx, WindowFromPoint, GA_ROOT,
+    HC_ACTION, MSG, MSLLHOOKSTRUCT, WH_MOUSE_LL, WM_LBUTTONUP, WM_RBUTTONDOWN, WM_RBUTTONUP,
+    WM_TIMER,
+};
+
+#[derive(Clone, Copy)]
+enum Click {
+    Target(HWND), // 点击位置下的窗口（可能是子控件）
+    Cancelled,    // 按下了右键，等待松开
+}
+
+// 钩子回调在安装钩子的线程中执行，通过线程局部变量传回结果
+thread_local! {
+    static CLICK: Cell<Option<Click>> = const { Cell::new(None) };
+}
+
+// 等待下一次鼠标左键点击，返回点击位置下的窗口。
+// 左键点击照常传给目标程序，使输入框获得焦点；右键取消，右键点击不会传给目标程序。
+// 点击本程序的控制台窗口会被忽略。
+pub fn wait_for_click(timeout: Duration) -> Result<HWND, String> {
+    CLICK.with(|click| click.set(None));
+    let timeout_ms = timeout.as_millis().clamp(1, u32::MAX as u128) as u32;
+
+    unsafe {
+        let hook = SetWindowsHookExW(
+            WH_MOUSE_LL,
+            Some(mouse_proc),
+            GetModuleHandleW(null_mut()),
+            0,
+        );
+        if hook.is_null() {
+            return Err("无法安装鼠标钩子".into());
+        }
+        let timer = SetTimer(null_mut(), 0, timeout_ms, None);
+
+        // 低级钩子要求安装线程处理消息，收到点击后钩子发出 WM_QUIT
+        let mut msg: MSG = std::mem::zeroed();
+        while GetMessageW(&mut msg, null_mut(), 0, 0) > 0 {
+            if msg.hwnd.is_null() && msg.message == WM_TIMER && msg.wParam == timer {
+                break;
+            }
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+
+        KillTimer(null_mut(), timer);
+        UnhookWindowsHookEx(hook);
+    }
+
+    match CLICK.with(|click| click.take()) {
+        Some(Click::Target(hwnd)) => Ok(hwnd),
+        Some(Click::Cancelled) => Err("已取消 (Cancelled)".into()),
+        None => Err(format!(
+            "{}秒内没有点击 (No click within {} seconds)",
+            timeout.as_secs(),
+            timeout.as_secs()
+        )),
+    }
+}
+
+unsafe extern "system" fn mouse_proc(code: c_int, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION {
+        let info = &*(lparam as *const MSLLHOOKSTRUCT);
+        let pending = CLICK.with(|click| click.get());
+        match (wparam as UINT, pending) {
+            (WM_LBUTTONUP, None) => {
+                let hwnd = WindowFromPoint(info.pt);
+                if !hwnd.is_null() && GetAncestor(hwnd, GA_ROOT) != GetConsoleWindow() {
+                    CLICK.with(|click| click.set(Some(Click::Target(hwnd))));
+                    PostQuitMessage(0);
+                }
+            }
+            (WM_RBUTTONDOWN, None) => {
+                CLICK.with(|click| click.set(Some(Click::Cancelled)));
+                return 1;
+            }
+            (WM_RBUTTONUP, Some(Click::Cancelled)) => {
+                PostQuitMessage(0);
+                return 1;
+            }
+            _ => {}
+        }
+    }
+    CallNextHookEx(null_mut(), code, wparam, lparam)
+}
@@ -1,78 +1,681 @@
 // src/clipboard.rs
-use log::{error, info};
+use crate::config::{ClipboardConfig, WatchConfig};
+use crate::history::{ClipboardHistory, HistoryEntry};
+use crate::richtext::{html_to_text, rtf_to_text};
+use crate::source::decode_bytes;
+use crate::window::process_exe_name;
+use log::{error, info, warn};
 use std::ffi::OsString;
+use std::fmt;
 use std::os::windows::ffi::OsStringExt;
+use std::path::PathBuf;
 use std::ptr::null_mut;
-use winapi::shared::minwindef::HGLOBAL;
-use winapi::um::winbase::{GlobalLock, GlobalUnlock};
-use winapi::um::winuser::{CloseClipboard, GetClipboardData, OpenClipboard, CF_UNICODETEXT};
+use std::thread;
+use std::time::{Duration, Instant};
+use winapi::shared::minwindef::{DWORD, HGLOBAL, UINT};
+use winapi::um::shellapi::{DragQueryFileW, HDROP};
+use winapi::um::winbase::{
+    GlobalAlloc, GlobalFree, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE,
+};
+use winapi::um::winnt::HANDLE;
+use winapi::um::winuser::{
+    CloseClipboard, EmptyClipboard, EnumClipboardFormats, GetClipboardData, GetClipboardOwner,
+    GetClipboardSequenceNumber, GetWindowThreadProcessId, IsClipboardFormatAvailable,
+    OpenClipboard, RegisterClipboardFormatW, SetClipboardData, CF_BITMAP, CF_DIB, CF_DIBV5,
+    CF_DSPBITMAP, CF_DSPENHMETAFILE, CF_DSPMETAFILEPICT, CF_ENHMETAFILE, CF_GDIOBJFIRST,
+    CF_GDIOBJLAST, CF_HDROP, CF_METAFILEPICT, CF_OWNERDISPLAY, CF_PALETTE, CF_PRIVATEFIRST,
+    CF_PRIVATELAST, CF_UNICODETEXT,
+};
+use zeroize::Zeroizing;
 
-pub struct ClipboardManager {
-    buffer: Option<String>,
+// 浏览器和 Office 使用的注册剪贴板格式
+const HTML_FORMAT: &str = "HTML Format";
+const RTF_FORMAT: &str = "Rich Text Format";
+
+// 密码管理器用来标记敏感内容的格式
+const EXCLUDE_MONITOR_FORMAT: &str = "ExcludeClipboardContentFromMonitorProcessing";
+const VIEWER_IGNORE_FORMAT: &str = "Clipboard Viewer Ignore";
+const CAN_INCLUDE_IN_HISTORY_FORMAT: &str = "CanIncludeInClipboardHistory";
+
+// 打开剪贴板重试间隔的上限
+const MAX_OPEN_BACKOFF: Duration = Duration::from_millis(200);
+
+// 读取剪贴板失败的原因
+#[derive(Debug)]
+pub enum ClipboardError {
+    Busy,          // 其他程序一直占用剪贴板
+    NoText,        // 剪贴板中没有可读取的文本
+    LockFailed,    // 无法锁定剪贴板内存
+    WriteFailed,   // 无法写入或清空剪贴板
+    FilesDisabled, // 剪贴板中是复制的文件，但未开启读取
+    File(String),  // 读取复制的文件失败
 }
 
-impl ClipboardManager {
-    pub fn new() -> Self {
-        ClipboardManager { buffer: None }
+impl fmt::Display for ClipboardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClipboardError::Busy => write!(f, "剪贴板被其他程序占用，请稍后重试"),
+            ClipboardError::NoText => write!(f, "剪贴板中没有文本数据"),
+            ClipboardError::LockFailed => write!(f, "无法锁定剪贴板内存"),
+            ClipboardError::WriteFailed => write!(f, "无法写入剪贴板"),
+            ClipboardError::FilesDisabled => write!(
+                f,
+                "剪贴板中是复制的文件，可在配置文件中设置 clipboard.read_files = true 读取文件内容"
+            ),
+            ClipboardError::File(e) => write!(f, "{}", e),
+        }
     }
+}
 
-    pub fn capture_clipboard(&mut self) -> Result<(), String> {
+impl From<ClipboardError> for String {
+    fn from(e: ClipboardError) -> Self {
+        e.to_string()
+    }
+}
+
+// 剪贴板中读到的内容
+enum ClipboardContent {
+    Text(Zeroizing<String>),
+    Files(Vec<PathBuf>), // 资源管理器中复制的文件
+}
+
+// 剪贴板中全部格式的副本，用于恢复剪贴板；丢弃时清零
+struct Snapshot {
+    formats: Vec<(UINT, Zeroizing<Vec<u8>>)>,
+}
+
+impl Snapshot {
+    // 保存剪贴板中所有能按字节复制的格式
+    fn take(timeout: Duration) -> Result<Snapshot, ClipboardError> {
+        open_clipboard(timeout)?;
+        let mut formats = Vec::new();
         unsafe {
-            // 打开剪贴板
-            if OpenClipboard(null_mut()) == 0 {
-                error!("无法打开剪贴板");
-                return Err("无法打开剪贴板".into());
+            let mut format = EnumClipboardFormats(0);
+            while format != 0 {
+                if copyable(format) {
+                    match read_global(format) {
+                        Some(bytes) => formats.push((format, bytes)),
+                        None => warn!("无法保存剪贴板格式 {}", format),
+                    }
+                }
+                format = EnumClipboardFormats(format);
             }
+            CloseClipboard();
+        }
+        Ok(Snapshot { formats })
+    }
 
-            // 获取剪贴板数据
-            let h_data = GetClipboardData(CF_UNICODETEXT);
-            if h_data.is_null() {
-                CloseClipboard();
-                error!("剪贴板中没有文本数据");
-                return Err("剪贴板中没有文本数据".into());
+    // 写回保存的全部格式，没有保存任何格式时清空剪贴板
+    fn restore(&self, timeout: Duration) -> Result<(), ClipboardError> {
+        open_clipboard(timeout)?;
+        unsafe {
+            let emptied = EmptyClipboard() != 0;
+            let failed = self
+                .formats
+                .iter()
+                .filter(|(format, bytes)| !write_global(*format, bytes))
+                .count();
+            CloseClipboard();
+            if !emptied || failed > 0 {
+                error!("无法恢复剪贴板内容，{} 种格式写入失败", failed);
+                return Err(ClipboardError::WriteFailed);
             }
+        }
+        Ok(())
+    }
+}
+
+// 捕获后清空了剪贴板，输入完成后要恢复的内容
+struct PendingRestore {
+    previous: Option<Zeroizing<String>>, // 捕获的内容之前的剪贴板文本，为空时保持剪贴板为空
+    sequence: DWORD, // 本程序最后一次修改剪贴板后的序号，变化说明其他程序修改过剪贴板
+}
+
+// 从剪贴板读到的文本
+struct Captured {
+    text: Zeroizing<String>,
+    source_app: Option<String>, // 复制内容的程序
+    excluded: bool,             // 密码管理器等程序标记为不应被监控或记录
+}
+
+pub struct ClipboardManager {
+    buffer: Option<Zeroizing<String>>, // 清除或丢弃时清零
+    history: ClipboardHistory,
+    settings: ClipboardConfig,
+    secret: bool,                            // 敏感内容模式下不记录历史
+    last_seen: Option<Zeroizing<String>>,    // 最近一次在剪贴板上看到的文本
+    previous: Option<Zeroizing<String>>,     // 在 last_seen 之前的剪贴板文本
+    pending_restore: Option<PendingRestore>, // 输入完成后要恢复的剪贴板内容
+}
+
+impl ClipboardManager {
+    pub fn with_history(history: ClipboardHistory) -> Self {
+        ClipboardManager {
+            buffer: None,
+            history,
+            settings: ClipboardConfig::default(),
+            secret: false,
+            last_seen: None,
+            previous: None,
+            pending_restore: None,
+        }
+    }
+
+    pub fn set_settings(&mut self, settings: ClipboardConfig) {
+        if settings.restore_after_typing && !settings.clear_after_capture {
+            warn!("restore_after_typing 需要同时开启 clear_after_capture，此项不起作用");
+        }
+        self.settings = settings;
+    }
+
+    pub fn set_secret(&mut self, secret: bool) {
+        self.secret = secret;
+    }
+
+    pub fn is_secret(&self) -> bool {
+        self.secret
+    }
+
+    pub fn capture_clipboard(&mut self) -> Result<(), ClipboardError> {
+        self.pending_restore = None;
+        let captured = read_clipboard(&self.settings)?;
+        self.remember(&captured.text);
 
-            // 锁定内存并获取指向文本的指针
-            let p_data = GlobalLock(h_data as HGLOBAL);
-            if p_data.is_null() {
-                CloseClipboard();
-                error!("无法锁定剪贴板内存");
-                return Err("无法锁定剪贴板内存".into());
+        if captured.excluded {
+            info!("剪贴板内容被标记为不记录，不加入历史");
+        }
+        self.store(captured.text, captured.source_app, !captured.excluded);
+        info!(
+            "已捕获剪贴板内容，长度: {}",
+            self.buffer.as_ref().unwrap().len()
+        );
+
+        if self.settings.clear_after_capture {
+            clear_clipboard(self.open_timeout())?;
+            self.last_seen = None;
+            // 输入完成后恢复捕获的内容之前的文本，而不是捕获的内容本身
+            if self.settings.restore_after_typing {
+                self.pending_restore = Some(PendingRestore {
+                    previous: self.previous.clone(),
+                    sequence: sequence_number(),
+                });
             }
+            info!("已清空系统剪贴板");
+        }
+        Ok(())
+    }
 
-            // 转换为Rust字符串
-            let wide_text = std::slice::from_raw_parts(
-                p_data as *const u16,
-                (0..)
-                    .take_while(|&i| *((p_data as *const u16).offset(i)) != 0)
-                    .count(),
-            );
+    // 自动捕获：只保存满足过滤条件且与缓冲区不同的内容，返回是否已保存
+    pub fn capture_filtered(&mut self, filter: &WatchConfig) -> Result<bool, ClipboardError> {
+        // 图片等非文本内容直接忽略，不记录错误
+        if !has_text_format() {
+            return Ok(false);
+        }
+        let captured = read_clipboard(&self.settings)?;
+        // 密码管理器标记的内容不自动捕获
+        if captured.excluded {
+            info!("剪贴板内容被标记为不监控，跳过自动捕获");
+            return Ok(false);
+        }
 
-            let result = OsString::from_wide(wide_text)
-                .to_string_lossy()
-                .into_owned();
+        // 与上次看到的内容相同，例如本程序恢复的剪贴板内容
+        let unchanged = self.last_seen.as_deref() == Some(&*captured.text);
+        self.remember(&captured.text);
+        if unchanged
+            || self.buffer.as_deref() == Some(&*captured.text)
+            || !filter.accepts(&captured.text, captured.source_app.as_deref())
+        {
+            return Ok(false);
+        }
+        info!(
+            "已自动捕获剪贴板内容，长度: {}，来源: {}",
+            captured.text.len(),
+            captured.source_app.as_deref().unwrap_or("?")
+        );
+        self.store(captured.text, captured.source_app, true);
+        Ok(true)
+    }
 
-            // 更新缓冲区
-            self.buffer = Some(result);
+    // 读取当前剪贴板文本，不改变缓冲区和历史
+    pub fn peek_clipboard(&self) -> Result<Zeroizing<String>, ClipboardError> {
+        read_clipboard(&self.settings).map(|captured| captured.text)
+    }
 
-            // 解锁并关闭
-            GlobalUnlock(h_data as HGLOBAL);
+    // 剪贴板内容能否完整保存和恢复。图元文件等以句柄保存的内容无法复制，恢复后会丢失
+    pub fn can_restore(&self) -> Result<bool, ClipboardError> {
+        open_clipboard(self.open_timeout())?;
+        unsafe {
+            let mut format = EnumClipboardFormats(0);
+            let mut lost = false;
+            while format != 0 && !lost {
+                lost = !copyable(format) && !synthesized(format);
+                format = EnumClipboardFormats(format);
+            }
             CloseClipboard();
+            Ok(!lost)
+        }
+    }
+
+    // 输入校验：保存剪贴板的全部格式后清空剪贴板，执行 copy（发送全选和复制按键）后
+    // 等待并读取复制的文本，然后恢复原来的内容。调用前应先用 can_restore 确认可以完整恢复。
+    // 恢复的内容记为最近看到的内容，自动捕获不会把读回的内容或恢复的内容当作新内容
+    pub fn read_back<F>(&mut self, copy: F, wait: Duration) -> Result<Zeroizing<String>, String>
+    where
+        F: FnOnce() -> Result<(), String>,
+    {
+        let timeout = self.open_timeout();
+        let saved_text = if has_text_format() {
+            read_clipboard(&self.settings)
+                .ok()
+                .map(|captured| captured.text)
+        } else {
+            None
+        };
+        let saved = Snapshot::take(timeout)?;
+        clear_clipboard(timeout)?;
+
+        let copied = copy().and_then(|_| {
+            let deadline = Instant::now() + wait;
+            while !has_text_format() {
+                if Instant::now() >= deadline {
+                    return Err("未能复制输入框内容，请检查校验按键设置".to_string());
+                }
+                thread::sleep(Duration::from_millis(50));
+            }
+            read_clipboard(&self.settings)
+                .map(|captured| captured.text)
+                .map_err(String::from)
+        });
 
-            info!(
-                "已捕获剪贴板内容，长度: {}",
-                self.buffer.as_ref().unwrap().len()
-            );
-            Ok(())
+        // 无论是否读取成功都恢复剪贴板
+        let restored = saved.restore(timeout);
+        self.last_seen = saved_text;
+        restored?;
+        // 读回前后的剪贴板内容相同，不算作其他程序的修改
+        if let Some(pending) = &mut self.pending_restore {
+            pending.sequence = sequence_number();
         }
+        info!("已读回输入框内容并恢复剪贴板");
+        copied
+    }
+
+    // 输入完成后恢复捕获前的剪贴板文本，没有时保持剪贴板为空。
+    // 其他程序在此期间修改过剪贴板时不恢复，避免覆盖新内容
+    pub fn restore_after_typing(&mut self) -> Result<(), ClipboardError> {
+        let pending = match self.pending_restore.take() {
+            Some(pending) => pending,
+            None => return Ok(()),
+        };
+        if sequence_number() != pending.sequence {
+            info!("剪贴板在输入期间已被修改，不再恢复");
+            return Ok(());
+        }
+
+        match pending.previous {
+            Some(text) => {
+                write_text(&text, self.open_timeout())?;
+                info!("已恢复之前的剪贴板内容");
+                self.last_seen = Some(text);
+            }
+            None => info!("没有之前的剪贴板内容，剪贴板保持为空"),
+        }
+        Ok(())
+    }
+
+    // 记录剪贴板上出现的新文本，保留它之前的内容用于恢复
+    fn remember(&mut self, text: &Zeroizing<String>) {
+        if self.last_seen.as_ref() != Some(text) {
+            self.previous = self.last_seen.replace(text.clone());
+        }
+    }
+
+    fn open_timeout(&self) -> Duration {
+        Duration::from_millis(self.settings.open_timeout_ms)
+    }
+
+    // 更新缓冲区和历史
+    fn store(&mut self, text: Zeroizing<String>, source_app: Option<String>, record: bool) {
+        if record && !self.secret {
+            self.history
+                .push(HistoryEntry::new(text.to_string(), source_app));
+        }
+        self.buffer = Some(text);
     }
 
     pub fn get_buffer(&self) -> Option<&String> {
-        self.buffer.as_ref()
+        self.buffer.as_deref()
     }
 
+    // 清空缓冲区，同时丢弃剪贴板记录中与缓冲区相同的副本和待恢复的内容
     pub fn clear_buffer(&mut self) {
-        self.buffer = None;
+        if let Some(buffer) = self.buffer.take() {
+            if self.last_seen.as_ref() == Some(&buffer) {
+                self.last_seen = None;
+            }
+            if self.previous.as_ref() == Some(&buffer) {
+                self.previous = None;
+            }
+        }
+        self.pending_restore = None;
         info!("缓冲区已清空");
     }
+
+    pub fn history(&self) -> &ClipboardHistory {
+        &self.history
+    }
+
+    pub fn history_mut(&mut self) -> &mut ClipboardHistory {
+        &mut self.history
+    }
+}
+
+// 读取剪贴板文本及复制它的程序
+fn read_clipboard(settings: &ClipboardConfig) -> Result<Captured, ClipboardError> {
+    open_clipboard(Duration::from_millis(settings.open_timeout_ms))?;
+    let (content, source_app, excluded) = unsafe {
+        let result = read_content(settings);
+
+        // 记录复制内容的程序，需在关闭剪贴板前获取
+        let source_app = clipboard_owner_exe();
+        let excluded = is_excluded();
+        CloseClipboard();
+
+        (result?, source_app, excluded)
+    };
+
+    // 文件在关闭剪贴板后再读取，避免长时间占用剪贴板
+    let text = match content {
+        ClipboardContent::Text(text) => text,
+        ClipboardContent::Files(paths) => Zeroizing::new(
+            read_files(&paths, settings.max_file_size).map_err(ClipboardError::File)?,
+        ),
+    };
+    Ok(Captured {
+        text,
+        source_app,
+        excluded,
+    })
+}
+
+// 密码管理器通过这些格式标记不应被剪贴板监控程序处理或记入历史的内容。调用前需已打开剪贴板
+unsafe fn is_excluded() -> bool {
+    if IsClipboardFormatAvailable(registered_format(EXCLUDE_MONITOR_FORMAT)) != 0
+        || IsClipboardFormatAvailable(registered_format(VIEWER_IGNORE_FORMAT)) != 0
+    {
+        return true;
+    }
+    // CanIncludeInClipboardHistory 的值为 0 时表示不允许记录；读取时在第一个0字节处截断
+    read_format_bytes(registered_format(CAN_INCLUDE_IN_HISTORY_FORMAT))
+        .is_some_and(|value| value.iter().all(|&b| b == 0))
+}
+
+fn sequence_number() -> DWORD {
+    unsafe { GetClipboardSequenceNumber() }
+}
+
+// 以 GDI 句柄或私有句柄保存的格式无法按字节复制
+fn copyable(format: UINT) -> bool {
+    !matches!(
+        format,
+        CF_BITMAP
+            | CF_PALETTE
+            | CF_METAFILEPICT
+            | CF_ENHMETAFILE
+            | CF_OWNERDISPLAY
+            | CF_DSPBITMAP
+            | CF_DSPMETAFILEPICT
+            | CF_DSPENHMETAFILE
+            | CF_PRIVATEFIRST..=CF_PRIVATELAST
+            | CF_GDIOBJFIRST..=CF_GDIOBJLAST
+    )
+}
+
+// 系统可以从其他格式合成的位图和调色板，恢复 CF_DIB 后会重新出现。调用前需已打开剪贴板
+unsafe fn synthesized(format: UINT) -> bool {
+    matches!(format, CF_BITMAP | CF_PALETTE)
+        && (IsClipboardFormatAvailable(CF_DIB) != 0 || IsClipboardFormatAvailable(CF_DIBV5) != 0)
+}
+
+// 把字节写入新的全局内存并放到剪贴板上。调用前需已打开剪贴板
+unsafe fn write_global(format: UINT, bytes: &[u8]) -> bool {
+    let h_data = GlobalAlloc(GMEM_MOVEABLE, bytes.len().max(1));
+    if h_data.is_null() {
+        return false;
+    }
+    let p_data = GlobalLock(h_data);
+    if p_data.is_null() {
+        GlobalFree(h_data);
+        return false;
+    }
+    std::ptr::copy_nonoverlapping(bytes.as_ptr(), p_data as *mut u8, bytes.len());
+    GlobalUnlock(h_data);
+
+    // 成功后内存归系统所有，失败时需自行释放
+    if SetClipboardData(format, h_data as HANDLE).is_null() {
+        GlobalFree(h_data);
+        return false;
+    }
+    true
+}
+
+// 用文本替换剪贴板内容
+fn write_text(text: &str, timeout: Duration) -> Result<(), ClipboardError> {
+    let bytes: Zeroizing<Vec<u8>> = Zeroizing::new(
+        text.encode_utf16()
+            .chain(Some(0))
+            .flat_map(u16::to_le_bytes)
+            .collect(),
+    );
+    open_clipboard(timeout)?;
+    unsafe {
+        let ok = EmptyClipboard() != 0 && write_global(CF_UNICODETEXT, &bytes);
+        CloseClipboard();
+        if !ok {
+            error!("无法写入剪贴板");
+            return Err(ClipboardError::WriteFailed);
+        }
+    }
+    Ok(())
+}
+
+// 清空系统剪贴板
+fn clear_clipboard(timeout: Duration) -> Result<(), ClipboardError> {
+    open_clipboard(timeout)?;
+    unsafe {
+        let ok = EmptyClipboard();
+        CloseClipboard();
+        if ok == 0 {
+            error!("无法清空剪贴板");
+            return Err(ClipboardError::WriteFailed);
+        }
+    }
+    Ok(())
+}
+
+// 打开剪贴板，被其他程序占用时按退避间隔重试直到超时
+fn open_clipboard(timeout: Duration) -> Result<(), ClipboardError> {
+    let deadline = Instant::now() + timeout;
+    let mut backoff = Duration::from_millis(10);
+    loop {
+        if unsafe { OpenClipboard(null_mut()) } != 0 {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            error!("剪贴板被其他程序占用");
+            return Err(ClipboardError::Busy);
+        }
+        thread::sleep(backoff.min(deadline - now));
+        backoff = (backoff * 2).min(MAX_OPEN_BACKOFF);
+    }
+}
+
+// 剪贴板中是否有可转换为文本的内容；复制的文件不算在内，避免自动捕获时读取文件
+fn has_text_format() -> bool {
+    unsafe {
+        IsClipboardFormatAvailable(CF_UNICODETEXT) != 0
+            || IsClipboardFormatAvailable(registered_format(HTML_FORMAT)) != 0
+            || IsClipboardFormatAvailable(registered_format(RTF_FORMAT)) != 0
+    }
+}
+
+// 读取文本；没有纯文本时转换 HTML 或 RTF 内容，或取得复制的文件列表。调用前需已打开剪贴板
+unsafe fn read_content(settings: &ClipboardConfig) -> Result<ClipboardContent, ClipboardError> {
+    if IsClipboardFormatAvailable(CF_UNICODETEXT) != 0 {
+        return read_unicode_text().map(ClipboardContent::Text);
+    }
+
+    if let Some(bytes) = read_format_bytes(registered_format(HTML_FORMAT)) {
+        info!("剪贴板中没有纯文本，已将HTML内容转换为文本");
+        let html = Zeroizing::new(String::from_utf8_lossy(&bytes).into_owned());
+        return Ok(ClipboardContent::Text(Zeroizing::new(html_to_text(&html))));
+    }
+    if let Some(bytes) = read_format_bytes(registered_format(RTF_FORMAT)) {
+        info!("剪贴板中没有纯文本，已将RTF内容转换为文本");
+        return Ok(ClipboardContent::Text(Zeroizing::new(rtf_to_text(&bytes))));
+    }
+
+    if IsClipboardFormatAvailable(CF_HDROP) != 0 {
+        if !settings.read_files {
+            error!("剪贴板中是复制的文件");
+            return Err(ClipboardError::FilesDisabled);
+        }
+        return read_file_list().map(ClipboardContent::Files);
+    }
+
+    error!("剪贴板中没有文本数据");
+    Err(ClipboardError::NoText)
+}
+
+// 读取 CF_HDROP 中的文件路径
+unsafe fn read_file_list() -> Result<Vec<PathBuf>, ClipboardError> {
+    let h_drop = GetClipboardData(CF_HDROP) as HDROP;
+    if h_drop.is_null() {
+        return Err(ClipboardError::NoText);
+    }
+
+    // 索引为 0xFFFFFFFF 时返回文件数量
+    let count = DragQueryFileW(h_drop, u32::MAX, null_mut(), 0);
+    let mut paths = Vec::new();
+    for i in 0..count {
+        let len = DragQueryFileW(h_drop, i, null_mut(), 0);
+        let mut buf = vec![0u16; len as usize + 1];
+        let copied = DragQueryFileW(h_drop, i, buf.as_mut_ptr(), buf.len() as UINT);
+        paths.push(PathBuf::from(OsString::from_wide(&buf[..copied as usize])));
+    }
+    Ok(paths)
+}
+
+// 读取复制的文本文件，多个文件的内容按顺序拼接
+fn read_files(paths: &[PathBuf], max_size: u64) -> Result<String, String> {
+    let mut total = 0u64;
+    let mut parts = Vec::new();
+    for path in paths {
+        let metadata = std::fs::metadata(path)
+            .map_err(|e| format!("无法读取文件 {}: {}", path.display(), e))?;
+        if !metadata.is_file() {
+            return Err(format!("不是文件: {}", path.display()));
+        }
+        total += metadata.len();
+        if total > max_size {
+            return Err(format!(
+                "复制的文件超过大小限制 ({} 字节): {}",
+                max_size,
+                path.display()
+            ));
+        }
+
+        let bytes =
+            std::fs::read(path).map_err(|e| format!("无法读取文件 {}: {}", path.display(), e))?;
+        let text = decode_bytes(&bytes);
+        if text.contains('\0') {
+            return Err(format!("不是文本文件: {}", path.display()));
+        }
+        info!("已读取复制的文件 {}，长度: {}", path.display(), text.len());
+        parts.push(text);
+    }
+
+    if parts.is_empty() {
+        return Err("复制的文件列表为空".into());
+    }
+    Ok(parts.join("\n"))
+}
+
+unsafe fn read_unicode_text() -> Result<Zeroizing<String>, ClipboardError> {
+    // 获取剪贴板数据
+    let h_data = GetClipboardData(CF_UNICODETEXT);
+    if h_data.is_null() {
+        error!("剪贴板中没有文本数据");
+        return Err(ClipboardError::NoText);
+    }
+
+    // 锁定内存并获取指向文本的指针
+    let p_data = GlobalLock(h_data as HGLOBAL);
+    if p_data.is_null() {
+        error!("无法锁定剪贴板内存");
+        return Err(ClipboardError::LockFailed);
+    }
+
+    // 转换为Rust字符串，读取范围不超过内存块大小，即使数据没有以NUL结尾
+    let units = GlobalSize(h_data as HGLOBAL) / 2;
+    let wide = std::slice::from_raw_parts(p_data as *const u16, units);
+    let len = wide.iter().position(|&c| c == 0).unwrap_or(units);
+
+    // 预先分配足够的空间，避免扩容时留下未清零的副本
+    let mut result = Zeroizing::new(String::with_capacity(len * 3));
+    for c in char::decode_utf16(wide[..len].iter().copied()) {
+        result.push(c.unwrap_or(char::REPLACEMENT_CHARACTER));
+    }
+
+    // 解锁
+    GlobalUnlock(h_data as HGLOBAL);
+    Ok(result)
+}
+
+// 读取注册格式的原始字节，到第一个NUL为止
+unsafe fn read_format_bytes(format: UINT) -> Option<Zeroizing<Vec<u8>>> {
+    if format == 0 || IsClipboardFormatAvailable(format) == 0 {
+        return None;
+    }
+    let mut bytes = read_global(format)?;
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    bytes.truncate(len);
+    Some(bytes)
+}
+
+// 读取以全局内存保存的格式的全部字节。调用前需已打开剪贴板
+unsafe fn read_global(format: UINT) -> Option<Zeroizing<Vec<u8>>> {
+    let h_data = GetClipboardData(format);
+    if h_data.is_null() {
+        return None;
+    }
+    let p_data = GlobalLock(h_data as HGLOBAL);
+    if p_data.is_null() {
+        return None;
+    }
+
+    let bytes = std::slice::from_raw_parts(p_data as *const u8, GlobalSize(h_data as HGLOBAL));
+    let result = Zeroizing::new(bytes.to_vec());
+
+    GlobalUnlock(h_data as HGLOBAL);
+    Some(result)
+}
+
+fn registered_format(name: &str) -> UINT {
+    let wide: Vec<u16> = name.encode_utf16().chain(Some(0)).collect();
+    unsafe { RegisterClipboardFormatW(wide.as_ptr()) }
+}
+
+// 当前剪贴板所有者的进程名
+fn clipboard_owner_exe() -> Option<String> {
+    unsafe {
+        let owner = GetClipboardOwner();
+        if owner.is_null() {
+            return None;
+        }
+        let mut pid: DWORD = 0;
+        GetWindowThreadProcessId(owner, &mut pid);
+        process_exe_name(pid)
+    }
 }
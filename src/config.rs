@@ -0,0 +1,664 @@
+// src/config.rs
+use crate::clipboard::ClipboardManager;
+use crate::history::{ClipboardHistory, DEFAULT_CAPACITY, HISTORY_FILE_NAME};
+use crate::input_sim::{Backend, IndentPolicy, InputSpeed, NewlinePolicy, TabPolicy};
+use crate::preflight::RedFlag;
+use crate::snippets::{SnippetStore, SNIPPETS_FILE_NAME};
+use crate::transform::Transform;
+use crate::verify::DeleteMode;
+use crate::window::WindowInfo;
+use log::{info, warn};
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+const DEFAULT_COUNTDOWN: u64 = 3;
+
+// 配置文件示例，`config init` 时写入
+pub const CONFIG_TEMPLATE: &str = r#"# 粘贴绕过工具配置文件 (Paste Bypass Tool configuration)
+
+# 默认使用的配置方案 (default profile)
+default_profile = "normal"
+# 开始输入前的倒计时秒数 (countdown in seconds before typing)
+countdown = 3
+# 输入片段前展开 {{date:%Y-%m-%d}}、{{env:USERNAME}}、{{clipboard}}、{{prompt:名称}}、{{uuid}}，\{{ 表示字面的 {{
+# (expand template variables in snippets before typing; \{{ stands for a literal {{)
+templates = true
+# 同时展开剪贴板、历史、文件和标准输入中的文本；--text 和菜单中输入的文本总是按原文输入
+# (also expand clipboard, history, file and stdin text; --text and text entered in the menu are always typed verbatim)
+templates_in_buffer = false
+
+[hotkeys]
+trigger = "Ctrl+Shift+V"
+leader = "Ctrl+Shift+Space"
+leader_timeout_ms = 1500
+
+[history]
+capacity = 20
+# 保存到配置目录下的 history.toml（未加密的纯文本），命令行的 history 子命令需要开启
+# (save to history.toml next to this file, as unencrypted plain text; required by the `history` subcommand)
+persist = false
+
+[watch]
+# 剪贴板内容变化时自动捕获 (capture automatically whenever the clipboard changes)
+enabled = false
+min_length = 1
+max_length = 100000
+# 忽略这些程序复制的内容 (ignore content copied by these programs)
+ignore_processes = ["KeePass.exe", "1Password.exe"]
+
+[clipboard]
+# 在资源管理器中复制文本文件时读取文件内容 (read the contents of text files copied in Explorer)
+read_files = false
+max_file_size = 1048576  # 字节 (bytes)
+# 剪贴板被其他程序占用时重试的时长 (how long to retry while another program holds the clipboard)
+open_timeout_ms = 1000
+# 捕获后清空系统剪贴板 (clear the system clipboard after capturing)
+clear_after_capture = false
+# 与 clear_after_capture 一起使用：输入完成后恢复捕获的内容之前的剪贴板文本，没有时剪贴板保持为空
+# (with clear_after_capture: once typing finishes, put back the text that was on the clipboard
+#  before the captured one, or leave the clipboard empty if there was none)
+restore_after_typing = false
+
+[secret]
+# 敏感内容模式：不显示内容、不记录历史，输入完成或超时后清除缓冲区
+# (secret mode: masked display, no history, buffer cleared after typing or timeout)
+enabled = false
+clear_after_secs = 60  # 0 表示不按时间清除 (0 disables the timeout)
+
+[preflight]
+# 输入前的检查，菜单选项 14 或 paste_bypass analyze 可查看结果
+# (checks before typing, shown by menu option 14 or paste_bypass analyze)
+max_line_width = 0       # 超过此宽度的行，0 表示不检查 (report longer lines, 0 disables)
+max_duration_secs = 0    # 预计耗时上限，0 表示不限 (duration limit, 0 disables)
+# 出现以下问题时阻止输入 (block typing when any of these is found):
+# non_bmp | control | unmappable | unrepresentable | long_lines | duration
+block_on = []
+
+[target]
+# 设置目标窗口后不再倒计时，而是在输入前激活该窗口（或等待它成为前台窗口），超时则报错
+# (with a target window set, the countdown is replaced by activating that window,
+#  or waiting for it to become the foreground window, failing after the timeout)
+# exe = "putty.exe"
+# title = "(?i)192\\.168\\.1\\.10"
+# click：不需要窗口条件，点击要输入的位置后开始输入，右键取消
+# (click: no conditions needed, typing starts after you click the field; right-click cancels)
+mode = "activate"        # activate | wait | click
+timeout_secs = 10
+settle_ms = 300          # 目标就绪后等待的毫秒数 (delay after the target is ready, in ms)
+# 在后台向目标窗口中拥有焦点的控件（点击模式下为点击的控件）发送 WM_CHAR 消息，不切换前台窗口；
+# 只有部分程序接受，见 README (post WM_CHAR messages to the focused control of the target window,
+# or the clicked control, without bringing it to the front; only some programs accept this, see README)
+background = false
+
+[verify]
+# 输入后全选并复制输入框内容，与发送的文本比较，报告第一个不一致的位置，然后恢复剪贴板。
+# 输入框在输入前必须为空；不要用于终端，Ctrl+C 会中断正在运行的程序
+# (after typing, select and copy the field, compare it with what was sent and report the first
+#  difference, then restore the clipboard. The field must be empty before typing; do not use
+#  with terminals, where Ctrl+C interrupts the running program)
+enabled = false
+select = ["Ctrl+A"]      # 单行输入框可用 ["Home", "Shift+End"] (for single-line fields)
+copy = ["Ctrl+C"]
+deselect = ["Ctrl+End"]
+wait_ms = 1000
+# 不一致时删除不一致处之后的内容，用较慢的方案从该处重新输入，而不是重新开始
+# (on a mismatch, delete everything from the first difference and retype from there at a slower
+#  profile instead of starting over)
+retries = 0              # 重新输入的次数，0 表示只报告 (number of attempts, 0 only reports)
+retry_profile = "slow"   # 只使用该方案的速度设置 (only its speed settings are used)
+delete = "backspace"     # backspace | select_to_end（从末尾选中后一次删除 / select back from the end, delete once）
+
+# 内置方案 slow / normal / fast / turbo 可以直接使用，也可以在这里覆盖
+# (built-in profiles slow / normal / fast / turbo can be used directly or overridden here)
+[profiles.console]
+delay_ms = 30
+batch_size = 1
+batch_delay_ms = 100
+newline = "enter"        # enter | shift_enter | space
+tab = "key"              # key | spaces:N | unicode
+backend = "virtual_key"  # unicode | virtual_key
+countdown = 5
+# 输入前的文本转换 (text transforms applied before typing):
+# ascii_punctuation | nfc | nfkc | strip_trailing_whitespace | expand_tabs:N | transliterate[:pinyin]
+transforms = ["ascii_punctuation", "strip_trailing_whitespace"]
+
+# 前台窗口匹配任意一条规则时自动使用该方案；同一条规则中的条件需全部满足
+# (the profile is picked automatically when the foreground window matches any rule;
+#  all conditions within one rule must match)
+[[profiles.console.match]]
+exe = "vmconnect.exe"
+
+[[profiles.console.match]]
+title = "(?i)kvm|ipmi"
+
+# 代码编辑器会自动缩进并补全括号，按原样输入会导致缩进叠加、括号重复
+# (code editors auto-indent and auto-close brackets, which doubles indentation and closers)
+[profiles.editor]
+indent = "replace"       # keep | strip | replace
+auto_close = "([{\""     # 编辑器自动补全的开始字符 (characters the editor auto-closes)
+
+[[profiles.editor.match]]
+exe = "Code.exe"
+"#;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub default_profile: String,
+    pub countdown: u64,
+    pub templates: bool,           // 输入片段前展开 {{date}} 等模板变量
+    pub templates_in_buffer: bool, // 同时展开剪贴板、历史、文件和标准输入中的文本
+    pub hotkeys: HotkeyConfig,
+    pub history: HistoryConfig,
+    pub watch: WatchConfig,
+    pub clipboard: ClipboardConfig,
+    pub secret: SecretConfig,
+    pub preflight: PreflightConfig,
+    pub target: TargetConfig,
+    pub verify: VerifyConfig,
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    pub capacity: usize,
+    pub persist: bool,
+}
+
+// 剪贴板读取选项
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ClipboardConfig {
+    pub read_files: bool,           // 复制文件时读取文件内容
+    pub max_file_size: u64,         // 读取文件的总大小上限（字节）
+    pub open_timeout_ms: u64,       // 剪贴板被占用时的重试时长
+    pub clear_after_capture: bool,  // 捕获后清空系统剪贴板
+    pub restore_after_typing: bool, // 输入完成后恢复捕获前的剪贴板文本
+}
+
+// 敏感内容模式
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SecretConfig {
+    pub enabled: bool,
+    pub clear_after_secs: u64, // 缓冲区保留的秒数，0 表示不限
+}
+
+// 输入前的检查
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PreflightConfig {
+    pub max_line_width: usize,  // 超过此宽度的行会被报告，0 表示不检查
+    pub max_duration_secs: u64, // 预计耗时上限，0 表示不限
+    pub block_on: Vec<RedFlag>, // 出现时阻止输入的问题
+}
+
+// 输入目标窗口，设置任一条件后代替倒计时：激活该窗口或等待它成为前台窗口
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TargetConfig {
+    pub exe: Option<String>,   // 进程可执行文件名，不区分大小写
+    pub class: Option<String>, // 窗口类名
+    #[serde(deserialize_with = "deserialize_regex")]
+    pub title: Option<Regex>, // 标题正则表达式
+    pub mode: TargetMode,
+    pub timeout_secs: u64, // 目标窗口未出现、未成为前台窗口或没有点击时的超时
+    pub settle_ms: u64,    // 目标窗口就绪后等待的毫秒数，然后开始输入
+    pub background: bool,  // 向目标控件投递键盘消息，不切换前台窗口
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetMode {
+    #[default]
+    Activate, // 查找并激活目标窗口
+    Wait,  // 等待用户切换到目标窗口
+    Click, // 等待用户点击要输入的位置
+}
+
+impl TargetConfig {
+    // 是否设置了窗口条件；点击模式下条件用于检查点击的窗口
+    pub fn is_set(&self) -> bool {
+        self.exe.is_some() || self.class.is_some() || self.title.is_some()
+    }
+
+    pub fn rule(&self) -> MatchRule {
+        MatchRule {
+            exe: self.exe.clone(),
+            class: self.class.clone(),
+            title: self.title.clone(),
+        }
+    }
+}
+
+// 输入后读回输入框内容进行校验
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct VerifyConfig {
+    pub enabled: bool,
+    pub select: Vec<String>,   // 选中输入框全部内容的按键，例如 ["Ctrl+A"]
+    pub copy: Vec<String>,     // 复制选中内容的按键
+    pub deselect: Vec<String>, // 校验后取消选中的按键，避免之后的输入覆盖全部内容
+    pub wait_ms: u64,          // 等待复制内容出现在剪贴板上的时长
+    pub retries: u32,          // 不一致时从不一致处重新输入的次数，0 表示不重试
+    pub retry_profile: String, // 重新输入时使用该方案的速度设置
+    pub delete: DeleteMode,    // 重新输入前删除不一致内容的方式
+}
+
+// 自动捕获的过滤条件
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WatchConfig {
+    pub enabled: bool,
+    pub min_length: usize,             // 最少字符数
+    pub max_length: usize,             // 最多字符数
+    pub ignore_processes: Vec<String>, // 进程可执行文件名，不区分大小写
+}
+
+impl WatchConfig {
+    // 判断新的剪贴板内容是否应被自动捕获
+    pub fn accepts(&self, text: &str, source_app: Option<&str>) -> bool {
+        let len = text.chars().count();
+        if len < self.min_length.max(1) || len > self.max_length {
+            return false;
+        }
+        !source_app.is_some_and(|app| {
+            self.ignore_processes
+                .iter()
+                .any(|ignored| ignored.eq_ignore_ascii_case(app))
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct HotkeyConfig {
+    pub trigger: String,
+    pub leader: String,
+    pub leader_timeout_ms: u64,
+}
+
+// 命名的输入配置方案，未填写的字段使用正常模式的值
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub delay_ms: u64,
+    pub batch_size: usize,
+    pub batch_delay_ms: u64,
+    pub newline: NewlinePolicy,
+    pub tab: TabPolicy,
+    pub backend: Backend,
+    pub countdown: Option<u64>,
+    pub transforms: Vec<Transform>, // 输入前依次执行的文本转换
+    pub indent: IndentPolicy,       // 编辑器自动缩进的补偿方式
+    pub auto_close: String,         // 编辑器会自动补全闭合字符的开始字符，例如 "([{\""
+    #[serde(rename = "match")]
+    pub rules: Vec<MatchRule>,
+}
+
+// 窗口匹配规则，未设置的条件视为满足
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MatchRule {
+    pub exe: Option<String>,   // 进程可执行文件名，不区分大小写
+    pub class: Option<String>, // 窗口类名
+    #[serde(deserialize_with = "deserialize_regex")]
+    pub title: Option<Regex>, // 标题正则表达式
+}
+
+impl MatchRule {
+    pub fn matches(&self, window: &WindowInfo) -> bool {
+        // 空规则不匹配任何窗口
+        if self.exe.is_none() && self.class.is_none() && self.title.is_none() {
+            return false;
+        }
+
+        let exe_ok = self.exe.as_ref().is_none_or(|exe| {
+            window
+                .exe
+                .as_ref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(exe))
+        });
+        let class_ok = self
+            .class
+            .as_ref()
+            .is_none_or(|class| *class == window.class);
+        let title_ok = self
+            .title
+            .as_ref()
+            .is_none_or(|title| title.is_match(&window.title));
+
+        exe_ok && class_ok && title_ok
+    }
+}
+
+fn deserialize_regex<'de, D>(deserializer: D) -> Result<Option<Regex>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let pattern = Option::<String>::deserialize(deserializer)?;
+    pattern
+        .map(|p| Regex::new(&p).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            default_profile: InputSpeed::Normal.name().into(),
+            countdown: DEFAULT_COUNTDOWN,
+            templates: true,
+            templates_in_buffer: false,
+            hotkeys: HotkeyConfig::default(),
+            history: HistoryConfig::default(),
+            watch: WatchConfig::default(),
+            clipboard: ClipboardConfig::default(),
+            secret: SecretConfig::default(),
+            preflight: PreflightConfig::default(),
+            target: TargetConfig::default(),
+            verify: VerifyConfig::default(),
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        HistoryConfig {
+            capacity: DEFAULT_CAPACITY,
+            persist: false,
+        }
+    }
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        ClipboardConfig {
+            read_files: false,
+            max_file_size: 1024 * 1024,
+            open_timeout_ms: 1000,
+            clear_after_capture: false,
+            restore_after_typing: false,
+        }
+    }
+}
+
+impl Default for SecretConfig {
+    fn default() -> Self {
+        SecretConfig {
+            enabled: false,
+            clear_after_secs: 60,
+        }
+    }
+}
+
+impl Default for TargetConfig {
+    fn default() -> Self {
+        TargetConfig {
+            exe: None,
+            class: None,
+            title: None,
+            mode: TargetMode::Activate,
+            timeout_secs: 10,
+            settle_ms: 300,
+            background: false,
+        }
+    }
+}
+
+impl Default for VerifyConfig {
+    fn default() -> Self {
+        VerifyConfig {
+            enabled: false,
+            select: vec!["Ctrl+A".into()],
+            copy: vec!["Ctrl+C".into()],
+            deselect: vec!["Ctrl+End".into()],
+            wait_ms: 1000,
+            retries: 0,
+            retry_profile: "slow".into(),
+            delete: DeleteMode::Backspace,
+        }
+    }
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        WatchConfig {
+            enabled: false,
+            min_length: 1,
+            max_length: 100_000,
+            ignore_processes: Vec::new(),
+        }
+    }
+}
+
+impl Default for HotkeyConfig {
+    fn default() -> Self {
+        HotkeyConfig {
+            trigger: "Ctrl+Shift+V".into(),
+            leader: "Ctrl+Shift+Space".into(),
+            leader_timeout_ms: 1500,
+        }
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile::from_speed(InputSpeed::Normal)
+    }
+}
+
+impl Profile {
+    pub fn from_speed(speed: InputSpeed) -> Self {
+        let (delay_ms, batch_size, batch_delay_ms) = speed.params();
+        Profile {
+            delay_ms,
+            batch_size,
+            batch_delay_ms,
+            newline: NewlinePolicy::Enter,
+            tab: TabPolicy::Unicode,
+            backend: Backend::Unicode,
+            countdown: None,
+            transforms: Vec::new(),
+            indent: IndentPolicy::Keep,
+            auto_close: String::new(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("无法读取配置文件 {}: {}", path.display(), e))?;
+        let config: Config = toml::from_str(&text)
+            .map_err(|e| format!("配置文件格式错误 {}: {}", path.display(), e))?;
+
+        if config.profile(&config.default_profile).is_none() {
+            return Err(format!("默认配置方案不存在: {}", config.default_profile));
+        }
+        if config.profile(&config.verify.retry_profile).is_none() {
+            return Err(format!(
+                "校验重新输入的配置方案不存在: {}",
+                config.verify.retry_profile
+            ));
+        }
+        Ok(config)
+    }
+
+    // 查找配置方案，文件中定义的方案优先于内置方案
+    pub fn profile(&self, name: &str) -> Option<Profile> {
+        if let Some(profile) = self.profiles.get(name) {
+            return Some(profile.clone());
+        }
+        name.parse::<InputSpeed>().ok().map(Profile::from_speed)
+    }
+
+    // 查找与窗口匹配的方案，按名称顺序取第一个
+    pub fn profile_for_window(&self, window: &WindowInfo) -> Option<(&str, &Profile)> {
+        self.profiles
+            .iter()
+            .find(|(_, profile)| profile.rules.iter().any(|rule| rule.matches(window)))
+            .map(|(name, profile)| (name.as_str(), profile))
+    }
+
+    // 所有可用方案名称：内置方案在前，其余按名称排序
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = InputSpeed::ALL
+            .iter()
+            .map(|speed| speed.name().to_string())
+            .collect();
+        for name in self.profiles.keys() {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        names
+    }
+
+    // 方案的倒计时，未设置时使用全局倒计时
+    pub fn countdown_for(&self, name: &str) -> u64 {
+        self.profile(name)
+            .and_then(|profile| profile.countdown)
+            .unwrap_or(self.countdown)
+    }
+}
+
+// 用户配置文件的默认位置：%APPDATA%\paste_bypass\config.toml
+pub fn default_config_path() -> Option<PathBuf> {
+    std::env::var_os("APPDATA").map(|dir| {
+        PathBuf::from(dir)
+            .join("paste_bypass")
+            .join(CONFIG_FILE_NAME)
+    })
+}
+
+// 按配置创建剪贴板历史，持久化文件与配置文件位于同一目录
+pub fn open_history(config: &Config, config_path: Option<&Path>) -> ClipboardHistory {
+    let dir = config_path.and_then(Path::parent);
+    match dir {
+        Some(dir) if config.history.persist => {
+            ClipboardHistory::persistent(config.history.capacity, dir.join(HISTORY_FILE_NAME))
+        }
+        _ => ClipboardHistory::new(config.history.capacity),
+    }
+}
+
+// 片段库保存在配置文件旁
+pub fn open_snippets(config_path: Option<&Path>) -> SnippetStore {
+    match config_path.and_then(Path::parent) {
+        Some(dir) => SnippetStore::open(dir.join(SNIPPETS_FILE_NAME)),
+        None => SnippetStore::new(),
+    }
+}
+
+// 按配置创建剪贴板管理器
+pub fn open_clipboard(config: &Config, config_path: Option<&Path>) -> ClipboardManager {
+    let mut clipboard = ClipboardManager::with_history(open_history(config, config_path));
+    clipboard.set_settings(config.clipboard.clone());
+    clipboard
+}
+
+// 写入配置文件模板，不覆盖已有文件
+pub fn write_template(path: &Path) -> Result<(), String> {
+    if path.exists() {
+        return Err(format!("配置文件已存在: {}", path.display()));
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("无法创建目录 {}: {}", dir.display(), e))?;
+    }
+    std::fs::write(path, CONFIG_TEMPLATE)
+        .map_err(|e| format!("无法写入配置文件 {}: {}", path.display(), e))
+}
+
+// 先写入同一目录下的临时文件并刷新到磁盘，再替换原文件。
+// 写入中途失败（例如磁盘已满或程序崩溃）时原文件保持不变
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    let tmp = path.with_file_name(name);
+
+    let written = File::create(&tmp).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    let result = written.and_then(|_| std::fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result
+}
+
+// 持有当前配置，并在文件修改后重新加载
+pub struct ConfigStore {
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    config: Config,
+}
+
+impl ConfigStore {
+    // 加载配置；文件不存在时使用默认配置
+    pub fn open(path: Option<PathBuf>) -> Result<Self, String> {
+        let mut store = ConfigStore {
+            path,
+            modified: None,
+            config: Config::default(),
+        };
+        store.reload()?;
+        Ok(store)
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn reload(&mut self) -> Result<(), String> {
+        let path = match &self.path {
+            Some(path) if path.exists() => path,
+            _ => {
+                self.config = Config::default();
+                self.modified = None;
+                return Ok(());
+            }
+        };
+
+        self.config = Config::load(path)?;
+        self.modified = modified_time(path);
+        info!("已加载配置文件: {}", path.display());
+        Ok(())
+    }
+
+    // 文件修改时间变化时重新加载，返回是否发生了重新加载
+    pub fn reload_if_changed(&mut self) -> bool {
+        let current = self.path.as_deref().and_then(modified_time);
+        if current == self.modified {
+            return false;
+        }
+
+        match self.reload() {
+            Ok(_) => true,
+            Err(e) => {
+                // 保留旧配置，避免写到一半的文件导致设置丢失
+                warn!("重新加载配置失败，继续使用旧配置: {}", e);
+                self.modified = current;
+                false
+            }
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
@@ -0,0 +1,183 @@
+// src/history.rs
+use crate::config;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const HISTORY_FILE_NAME: &str = "history.toml";
+pub const DEFAULT_CAPACITY: usize = 20;
+
+// 一条剪贴板历史记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub text: String,
+    pub captured_at: u64,           // Unix 时间戳（秒）
+    pub source_app: Option<String>, // 复制内容的程序，例如 "chrome.exe"
+}
+
+impl HistoryEntry {
+    pub fn new(text: String, source_app: Option<String>) -> Self {
+        let captured_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        HistoryEntry {
+            text,
+            captured_at,
+            source_app,
+        }
+    }
+
+    pub fn preview(&self, max_chars: usize) -> String {
+        preview(&self.text, max_chars)
+    }
+
+    // 距今时间的简短描述
+    pub fn age(&self) -> String {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let secs = now.saturating_sub(self.captured_at);
+        match secs {
+            0..=59 => format!("{}秒前 ({}s ago)", secs, secs),
+            60..=3599 => format!("{}分钟前 ({}m ago)", secs / 60, secs / 60),
+            3600..=86399 => format!("{}小时前 ({}h ago)", secs / 3600, secs / 3600),
+            _ => format!("{}天前 ({}d ago)", secs / 86400, secs / 86400),
+        }
+    }
+}
+
+// 单行预览，换行和制表符转义后截断
+pub fn preview(text: &str, max_chars: usize) -> String {
+    let mut preview = String::new();
+    for (i, c) in text.chars().enumerate() {
+        if i >= max_chars {
+            preview.push('…');
+            break;
+        }
+        match c {
+            '\n' => preview.push_str("\\n"),
+            '\r' => {}
+            '\t' => preview.push_str("\\t"),
+            _ => preview.push(c),
+        }
+    }
+    preview
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct HistoryFile {
+    #[serde(default)]
+    entry: Vec<HistoryEntry>,
+}
+
+// 有界的剪贴板历史，最新的记录在最前面
+pub struct ClipboardHistory {
+    entries: VecDeque<HistoryEntry>,
+    capacity: usize,
+    path: Option<PathBuf>, // 设置后每次变化都写入文件
+}
+
+impl ClipboardHistory {
+    pub fn new(capacity: usize) -> Self {
+        ClipboardHistory {
+            entries: VecDeque::new(),
+            capacity: capacity.max(1),
+            path: None,
+        }
+    }
+
+    // 从文件加载历史并在之后的每次变化时保存
+    pub fn persistent(capacity: usize, path: PathBuf) -> Self {
+        let mut history = ClipboardHistory::new(capacity);
+        if path.exists() {
+            match std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| toml::from_str::<HistoryFile>(&text).map_err(|e| e.to_string()))
+            {
+                Ok(file) => {
+                    history.entries = file.entry.into_iter().collect();
+                    history.entries.truncate(history.capacity);
+                    info!("已加载 {} 条剪贴板历史", history.entries.len());
+                }
+                Err(e) => warn!("无法读取剪贴板历史 {}: {}", path.display(), e),
+            }
+        }
+        history.path = Some(path);
+        history
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        if self.entries.len() > self.capacity {
+            self.entries.truncate(self.capacity);
+            self.save();
+        }
+    }
+
+    // 添加记录；与已有记录相同的文本会被移到最前面
+    pub fn push(&mut self, entry: HistoryEntry) {
+        if entry.text.is_empty() {
+            return;
+        }
+        self.entries.retain(|e| e.text != entry.text);
+        self.entries.push_front(entry);
+        self.entries.truncate(self.capacity);
+        self.save();
+    }
+
+    // 获取第 n 新的记录（从1开始）
+    pub fn get(&self, n: usize) -> Option<&HistoryEntry> {
+        n.checked_sub(1).and_then(|i| self.entries.get(i))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // 打印带编号的历史列表
+    pub fn print(&self) {
+        if self.entries.is_empty() {
+            println!("剪贴板历史为空 (Clipboard history is empty)");
+            return;
+        }
+        for (i, entry) in self.entries.iter().enumerate() {
+            println!(
+                "{:>2}. [{}] [{}] {}",
+                i + 1,
+                entry.age(),
+                entry.source_app.as_deref().unwrap_or("?"),
+                entry.preview(60)
+            );
+        }
+    }
+
+    fn save(&self) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+
+        let file = HistoryFile {
+            entry: self.entries.iter().cloned().collect(),
+        };
+        let result = toml::to_string(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+                }
+                config::write_atomic(path, text.as_bytes()).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            warn!("无法保存剪贴板历史 {}: {}", path.display(), e);
+        }
+    }
+}
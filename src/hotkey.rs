@@ -1,34 +1,47 @@
 // src/hotkey.rs
-use log::{error, info};
-use std::sync::mpsc::{channel, Sender};
+use log::{error, info, warn};
+use std::sync::mpsc::channel;
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Duration;
 use winapi::ctypes::c_int;
+use winapi::shared::minwindef::DWORD;
 use winapi::shared::windef::HWND;
+use winapi::um::processthreadsapi::GetCurrentThreadId;
 use winapi::um::winuser::{
-    DispatchMessageW, GetMessageW, RegisterHotKey, TranslateMessage, UnregisterHotKey, MSG,
-    WM_HOTKEY,
+    DispatchMessageW, GetMessageW, KillTimer, PeekMessageW, PostThreadMessageW, RegisterHotKey,
+    SetTimer, TranslateMessage, UnregisterHotKey, MSG, PM_NOREMOVE, WM_HOTKEY, WM_QUIT, WM_TIMER,
 };
 
 // 修饰键
 pub const MOD_CONTROL: c_int = 0x0002;
 pub const MOD_SHIFT: c_int = 0x0004;
 pub const MOD_ALT: c_int = 0x0001;
+pub const MOD_WIN: c_int = 0x0008;
+pub const MOD_NOREPEAT: c_int = 0x4000;
+
+// 热键ID
+const HOTKEY_ID: c_int = 1;
+const LEADER_ID: c_int = 2;
+const FOLLOW_BASE_ID: c_int = 100;
 
 type HotkeyCallback = Arc<Mutex<Box<dyn Fn() + Send>>>;
+type SequenceCallback = Arc<Mutex<Box<dyn Fn(c_int) + Send>>>;
+
+// 每个注册对应一个带消息循环的线程
+struct HotkeyWorker {
+    thread_id: DWORD,
+    thread_handle: thread::JoinHandle<()>,
+}
 
 pub struct HotkeyManager {
-    thread_handle: Option<thread::JoinHandle<()>>,
-    registered: bool,
-    exit_sender: Option<Sender<()>>,
+    workers: Vec<HotkeyWorker>,
 }
 
 impl HotkeyManager {
     pub fn new() -> Self {
         HotkeyManager {
-            thread_handle: None,
-            registered: false,
-            exit_sender: None,
+            workers: Vec::new(),
         }
     }
 
@@ -36,88 +49,325 @@ impl HotkeyManager {
     where
         F: Fn() + Send + 'static,
     {
-        if self.registered {
-            return Err("热键已注册".into());
-        }
-
         // 使用Arc<Mutex<>>包装回调函数，使其可以跨线程安全共享
-        let callback = Arc::new(Mutex::new(Box::new(callback) as Box<dyn Fn() + Send>));
+        let callback: HotkeyCallback = Arc::new(Mutex::new(Box::new(callback)));
 
-        // 创建一个通道用于通知线程退出
-        let (exit_tx, exit_rx) = channel();
-        self.exit_sender = Some(exit_tx);
-
-        // 线程安全地克隆回调，以便在线程内使用
-        let callback_clone = Arc::clone(&callback);
-
-        let thread_handle = thread::spawn(move || {
-            unsafe {
-                // 注册热键 (ID: 1)
-                if RegisterHotKey(std::ptr::null_mut(), 1, modifiers as u32, key as u32) == 0 {
-                    error!("无法注册热键");
-                    return;
+        self.spawn_worker(
+            move || {
+                unsafe {
+                    // 注册热键
+                    if RegisterHotKey(
+                        std::ptr::null_mut(),
+                        HOTKEY_ID,
+                        (modifiers | MOD_NOREPEAT) as u32,
+                        key as u32,
+                    ) == 0
+                    {
+                        error!("无法注册热键");
+                        return Err("无法注册热键，可能已被其他程序占用".into());
+                    }
                 }
-
                 info!("热键注册成功");
+                Ok(())
+            },
+            move |msg| {
+                if msg.message == WM_HOTKEY && msg.wParam == HOTKEY_ID as usize {
+                    info!("热键触发");
 
-                // 消息循环
-                let mut msg: MSG = std::mem::zeroed();
-                let mut should_exit = false;
-
-                while !should_exit && GetMessageW(&mut msg, 0 as HWND, 0, 0) != 0 {
-                    // 检查是否收到退出信号
-                    if let Ok(_) = exit_rx.try_recv() {
-                        should_exit = true;
-                        break;
+                    // 执行回调
+                    if let Ok(callback) = callback.lock() {
+                        (*callback)();
                     }
+                }
+            },
+            || unsafe {
+                UnregisterHotKey(std::ptr::null_mut(), HOTKEY_ID);
+            },
+        )
+    }
+
+    // 注册前导键序列：先按前导组合键，再在超时时间内按一个后续键。
+    // 后续键只在前导键触发后临时注册，所以多个动作只占用一个全局组合键。
+    // 后续键不带修饰键，等待期间在所有程序中按这些键都会被拦截，直到按下其中一个或超时
+    pub fn register_sequence<F>(
+        &mut self,
+        modifiers: c_int,
+        key: c_int,
+        follow_keys: &[c_int],
+        timeout: Duration,
+        callback: F,
+    ) -> Result<(), String>
+    where
+        F: Fn(c_int) + Send + 'static,
+    {
+        if follow_keys.is_empty() {
+            return Err("后续键列表为空".into());
+        }
+
+        let callback: SequenceCallback = Arc::new(Mutex::new(Box::new(callback)));
+        let follow_keys = follow_keys.to_vec();
+        let follow_count = follow_keys.len();
+        let timeout_ms = timeout.as_millis().min(u32::MAX as u128) as u32;
 
-                    if msg.message == WM_HOTKEY && msg.wParam == 1 {
-                        info!("热键触发");
+        // 序列状态只在热键线程内使用
+        let mut timer_id: usize = 0;
 
-                        // 执行回调
-                        if let Ok(callback) = callback_clone.lock() {
-                            (*callback)();
+        self.spawn_worker(
+            move || {
+                unsafe {
+                    if RegisterHotKey(
+                        std::ptr::null_mut(),
+                        LEADER_ID,
+                        (modifiers | MOD_NOREPEAT) as u32,
+                        key as u32,
+                    ) == 0
+                    {
+                        error!("无法注册前导键");
+                        return Err("无法注册前导键，可能已被其他程序占用".into());
+                    }
+                }
+                info!("前导键注册成功，后续键数量: {}", follow_count);
+                Ok(())
+            },
+            move |msg| unsafe {
+                match msg.message {
+                    WM_HOTKEY if msg.wParam == LEADER_ID as usize => {
+                        if timer_id != 0 {
+                            // 已处于等待状态，重新计时
+                            KillTimer(std::ptr::null_mut(), timer_id);
+                        } else {
+                            for (i, &k) in follow_keys.iter().enumerate() {
+                                if RegisterHotKey(
+                                    std::ptr::null_mut(),
+                                    FOLLOW_BASE_ID + i as c_int,
+                                    MOD_NOREPEAT as u32,
+                                    k as u32,
+                                ) == 0
+                                {
+                                    warn!("无法注册后续键: 0x{:02X}", k);
+                                }
+                            }
+                        }
+                        timer_id = SetTimer(std::ptr::null_mut(), 0, timeout_ms, None);
+                        info!("前导键触发，等待后续键");
+                    }
+                    WM_HOTKEY if timer_id != 0 && msg.wParam >= FOLLOW_BASE_ID as usize => {
+                        let index = msg.wParam - FOLLOW_BASE_ID as usize;
+                        disarm_sequence(&follow_keys, &mut timer_id);
+                        if let Some(&k) = follow_keys.get(index) {
+                            info!("序列热键触发: 0x{:02X}", k);
+                            if let Ok(callback) = callback.lock() {
+                                (*callback)(k);
+                            }
                         }
                     }
+                    WM_TIMER if timer_id != 0 && msg.wParam == timer_id => {
+                        disarm_sequence(&follow_keys, &mut timer_id);
+                        info!("序列热键超时");
+                    }
+                    _ => {}
+                }
+            },
+            move || unsafe {
+                for i in 0..follow_count {
+                    UnregisterHotKey(std::ptr::null_mut(), FOLLOW_BASE_ID + i as c_int);
+                }
+                UnregisterHotKey(std::ptr::null_mut(), LEADER_ID);
+            },
+        )
+    }
+
+    // 启动热键线程：setup 在线程内注册热键，handler 处理每条消息，cleanup 在退出前注销
+    fn spawn_worker<S, H, C>(&mut self, setup: S, mut handler: H, cleanup: C) -> Result<(), String>
+    where
+        S: FnOnce() -> Result<(), String> + Send + 'static,
+        H: FnMut(&MSG) + Send + 'static,
+        C: FnOnce() + Send + 'static,
+    {
+        // 创建一个通道用于回报注册结果
+        let (ready_tx, ready_rx) = channel();
+
+        let thread_handle = thread::spawn(move || {
+            unsafe {
+                // 确保线程消息队列已创建，以便接收 PostThreadMessageW
+                let mut msg: MSG = std::mem::zeroed();
+                PeekMessageW(&mut msg, 0 as HWND, 0, 0, PM_NOREMOVE);
+
+                let result = setup();
+                let ok = result.is_ok();
+                let _ = ready_tx.send(result.map(|_| GetCurrentThreadId()));
+                if !ok {
+                    return;
+                }
+
+                // 消息循环，收到 WM_QUIT 时退出
+                while GetMessageW(&mut msg, 0 as HWND, 0, 0) > 0 {
+                    handler(&msg);
 
                     TranslateMessage(&msg);
                     DispatchMessageW(&msg);
                 }
 
-                // 注销热键
-                UnregisterHotKey(std::ptr::null_mut(), 1);
+                cleanup();
             }
         });
 
-        self.thread_handle = Some(thread_handle);
-        self.registered = true;
-        Ok(())
+        match ready_rx.recv() {
+            Ok(Ok(thread_id)) => {
+                self.workers.push(HotkeyWorker {
+                    thread_id,
+                    thread_handle,
+                });
+                Ok(())
+            }
+            Ok(Err(e)) => {
+                let _ = thread_handle.join();
+                Err(e)
+            }
+            Err(_) => Err("热键线程意外退出".into()),
+        }
     }
 
     pub fn unregister(&mut self) -> Result<(), String> {
-        if !self.registered {
+        if self.workers.is_empty() {
             return Ok(());
         }
 
         // 发送退出信号
-        if let Some(sender) = &self.exit_sender {
-            if let Err(_) = sender.send(()) {
+        for worker in self.workers.drain(..) {
+            if unsafe { PostThreadMessageW(worker.thread_id, WM_QUIT, 0, 0) } == 0 {
                 error!("无法发送退出信号");
+                continue;
             }
+            let _ = worker.thread_handle.join();
         }
 
-        self.registered = false;
-        self.exit_sender = None;
-
         info!("热键已注销");
         Ok(())
     }
 }
 
+// 解析形如 "Ctrl+Shift+V" 的热键描述，返回 (修饰键, 虚拟键码)
+pub fn parse_hotkey(text: &str) -> Result<(c_int, c_int), String> {
+    let mut modifiers = 0;
+    let mut key = None;
+
+    for part in text.split('+').map(str::trim) {
+        match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => modifiers |= MOD_CONTROL,
+            "shift" => modifiers |= MOD_SHIFT,
+            "alt" => modifiers |= MOD_ALT,
+            "win" | "super" => modifiers |= MOD_WIN,
+            _ => {
+                if key.is_some() {
+                    return Err(format!("热键只能包含一个普通键: {}", text));
+                }
+                key = Some(parse_key(part).ok_or_else(|| format!("无法识别的按键: {}", part))?);
+            }
+        }
+    }
+
+    match key {
+        Some(key) => Ok((modifiers, key)),
+        None => Err(format!("热键缺少普通键: {}", text)),
+    }
+}
+
+// 将按键名称转换为虚拟键码
+pub fn parse_key(name: &str) -> Option<c_int> {
+    let upper = name.to_ascii_uppercase();
+    let bytes = upper.as_bytes();
+
+    // 单个字母或数字与虚拟键码相同
+    if bytes.len() == 1 && bytes[0].is_ascii_alphanumeric() {
+        return Some(bytes[0] as c_int);
+    }
+
+    // F1 - F24
+    if let Some(n) = upper
+        .strip_prefix('F')
+        .and_then(|n| n.parse::<c_int>().ok())
+    {
+        if (1..=24).contains(&n) {
+            return Some(0x70 + n - 1);
+        }
+    }
+
+    let vk = match upper.as_str() {
+        "SPACE" => 0x20,
+        "ENTER" | "RETURN" => 0x0D,
+        "TAB" => 0x09,
+        "ESC" | "ESCAPE" => 0x1B,
+        "BACKSPACE" => 0x08,
+        "INSERT" | "INS" => 0x2D,
+        "DELETE" | "DEL" => 0x2E,
+        "HOME" => 0x24,
+        "END" => 0x23,
+        "PAGEUP" => 0x21,
+        "PAGEDOWN" => 0x22,
+        "LEFT" => 0x25,
+        "UP" => 0x26,
+        "RIGHT" => 0x27,
+        "DOWN" => 0x28,
+        _ => return None,
+    };
+    Some(vk)
+}
+
+// 结束序列等待状态：注销临时注册的后续键并停止计时器
+unsafe fn disarm_sequence(follow_keys: &[c_int], timer_id: &mut usize) {
+    KillTimer(std::ptr::null_mut(), *timer_id);
+    *timer_id = 0;
+    for i in 0..follow_keys.len() {
+        UnregisterHotKey(std::ptr::null_mut(), FOLLOW_BASE_ID + i as c_int);
+    }
+}
+
 impl Drop for HotkeyManager {
     fn drop(&mut self) {
-        if self.registered {
-            let _ = self.unregister();
-        }
+        let _ = self.unregister();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modifiers_are_combined() {
+        assert_eq!(
+            parse_hotkey("Ctrl+Shift+V"),
+            Ok((MOD_CONTROL | MOD_SHIFT, 'V' as c_int))
+        );
+        assert_eq!(
+            parse_hotkey("Control + Alt + Win + F12"),
+            Ok((MOD_CONTROL | MOD_ALT | MOD_WIN, 0x7B))
+        );
+        assert_eq!(parse_hotkey("super+1"), Ok((MOD_WIN, '1' as c_int)));
+        assert_eq!(parse_hotkey("Space"), Ok((0, 0x20)));
+    }
+
+    #[test]
+    fn names_are_case_insensitive() {
+        assert_eq!(
+            parse_hotkey("ctrl+shift+space"),
+            parse_hotkey("CTRL+SHIFT+SPACE")
+        );
+        assert_eq!(parse_key("v"), Some('V' as c_int));
+        assert_eq!(parse_key("PageDown"), Some(0x22));
+        assert_eq!(parse_key("esc"), parse_key("Escape"));
+        assert_eq!(parse_key("f1"), Some(0x70));
+    }
+
+    #[test]
+    fn invalid_names_are_rejected() {
+        assert_eq!(parse_key("F0"), None);
+        assert_eq!(parse_key("F25"), None);
+        assert_eq!(parse_key("Hyper"), None);
+        assert_eq!(parse_key(""), None);
+        assert!(parse_hotkey("Ctrl+Hyper").is_err());
+        assert!(parse_hotkey("Ctrl+A+B").is_err());
+        assert!(parse_hotkey("Ctrl+Shift").is_err());
+        assert!(parse_hotkey("Ctrl+").is_err());
     }
 }
@@ -1,11 +1,27 @@
 // src/input_sim.rs
-use log::{error, info};
+use crate::config::Profile;
+use crate::transform::Transform;
+use log::{error, info, warn};
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
-use winapi::shared::minwindef::{UINT, WORD};
+use winapi::shared::minwindef::{DWORD, LPARAM, UINT, WORD, WPARAM};
+use winapi::shared::windef::HWND;
+use winapi::um::errhandlingapi::GetLastError;
 use winapi::um::winuser::{
-    SendInput, INPUT, INPUT_KEYBOARD, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, VK_RETURN,
+    MapVirtualKeyW, PostMessageW, SendInput, VkKeyScanW, INPUT, INPUT_KEYBOARD,
+    KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, MAPVK_VK_TO_VSC, VK_CONTROL,
+    VK_DELETE, VK_DOWN, VK_END, VK_HOME, VK_INSERT, VK_LEFT, VK_MENU, VK_NEXT, VK_PRIOR, VK_RETURN,
+    VK_RIGHT, VK_SHIFT, VK_TAB, VK_UP, WM_CHAR, WM_KEYDOWN, WM_KEYUP,
 };
+use zeroize::{Zeroize, Zeroizing};
+
+// 按键修饰标记
+pub const MOD_SHIFT: u8 = 0x01;
+pub const MOD_CONTROL: u8 = 0x02;
+pub const MOD_ALT: u8 = 0x04;
 
 // 定义输入速度模式
 #[derive(Debug, Clone, Copy)]
@@ -16,68 +32,156 @@ pub enum InputSpeed {
     Turbo,  // 极速 - 批处理大量字符，几乎无延迟
 }
 
-pub struct InputSimulator {
-    delay_ms: u64,          // 字符间延迟（毫秒）
-    batch_size: usize,      // 批处理字符数量
-    batch_delay_ms: u64,    // 批次间延迟（毫秒）
-    speed_mode: InputSpeed, // 速度模式
+impl std::str::FromStr for InputSpeed {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "slow" => Ok(InputSpeed::Slow),
+            "normal" => Ok(InputSpeed::Normal),
+            "fast" => Ok(InputSpeed::Fast),
+            "turbo" => Ok(InputSpeed::Turbo),
+            _ => Err(format!("未知的速度模式: {} (slow/normal/fast/turbo)", s)),
+        }
+    }
 }
 
-impl InputSimulator {
-    pub fn new() -> Self {
-        InputSimulator {
-            delay_ms: 10,      // 默认字符延迟10毫秒
-            batch_size: 1,     // 默认单字符处理
-            batch_delay_ms: 0, // 默认无批次延迟
-            speed_mode: InputSpeed::Normal,
+impl InputSpeed {
+    pub const ALL: [InputSpeed; 4] = [
+        InputSpeed::Slow,
+        InputSpeed::Normal,
+        InputSpeed::Fast,
+        InputSpeed::Turbo,
+    ];
+
+    // 返回 (字符延迟, 批处理大小, 批次延迟)
+    pub fn params(&self) -> (u64, usize, u64) {
+        match self {
+            InputSpeed::Slow => (20, 1, 300),
+            InputSpeed::Normal => (10, 5, 50),
+            InputSpeed::Fast => (2, 20, 20),
+            InputSpeed::Turbo => (0, 50, 10),
         }
     }
 
-    pub fn with_delay(delay_ms: u64) -> Self {
-        InputSimulator {
-            delay_ms,
-            batch_size: 1,
-            batch_delay_ms: 0,
-            speed_mode: InputSpeed::Normal,
+    pub fn name(&self) -> &'static str {
+        match self {
+            InputSpeed::Slow => "slow",
+            InputSpeed::Normal => "normal",
+            InputSpeed::Fast => "fast",
+            InputSpeed::Turbo => "turbo",
         }
     }
+}
 
-    // 设置速度模式
-    pub fn set_speed_mode(&mut self, mode: InputSpeed) {
-        self.speed_mode = mode;
-        match mode {
-            InputSpeed::Slow => {
-                self.delay_ms = 20;
-                self.batch_size = 1;
-                self.batch_delay_ms = 300;
-            }
-            InputSpeed::Normal => {
-                self.delay_ms = 10;
-                self.batch_size = 5;
-                self.batch_delay_ms = 50;
-            }
-            InputSpeed::Fast => {
-                self.delay_ms = 2;
-                self.batch_size = 20;
-                self.batch_delay_ms = 20;
-            }
-            InputSpeed::Turbo => {
-                self.delay_ms = 0;
-                self.batch_size = 50;
-                self.batch_delay_ms = 10;
-            }
+// 换行符的输出方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NewlinePolicy {
+    #[default]
+    Enter, // 回车键
+    ShiftEnter, // Shift+回车，适合回车即发送的聊天窗口
+    Space,      // 替换为空格，适合单行输入框
+}
+
+// 输出后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Backend {
+    #[default]
+    Unicode, // KEYEVENTF_UNICODE，支持任意字符
+    VirtualKey, // 按当前键盘布局发送虚拟键，适合虚拟机和远程控制台，无法映射的字符回退到 Unicode
+}
+
+// 编辑器自动缩进的补偿方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndentPolicy {
+    #[default]
+    Keep, // 原样输入
+    Strip,   // 不输入行首缩进，由编辑器自动缩进
+    Replace, // 换行后按 Shift+Home 选中编辑器插入的缩进，再输入原文的缩进
+}
+
+// 制表符的输出方式，对所有输出后端一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(try_from = "String")]
+pub enum TabPolicy {
+    #[default]
+    Unicode, // 以 Unicode 方式发送制表符，与输出后端无关
+    Key,           // Tab 键，部分程序会切换焦点
+    Spaces(usize), // 替换为 N 个空格
+}
+
+impl TryFrom<String> for TabPolicy {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, Self::Error> {
+        match spec.trim() {
+            "key" => Ok(TabPolicy::Key),
+            "unicode" => Ok(TabPolicy::Unicode),
+            "spaces" => Ok(TabPolicy::Spaces(4)),
+            other => other
+                .strip_prefix("spaces:")
+                .and_then(|n| n.trim().parse().ok())
+                .map(TabPolicy::Spaces)
+                .ok_or_else(|| format!("未知的制表符策略: {} (key/spaces:N/unicode)", spec)),
         }
+    }
+}
 
-        info!(
-            "速度模式设置为: {:?}, 延迟: {}ms, 批处理: {}, 批次延迟: {}ms",
-            self.speed_mode, self.delay_ms, self.batch_size, self.batch_delay_ms
-        );
+// 一次按键操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyStroke {
+    Char(char),    // 输出一个字符
+    Unicode(char), // 始终以 Unicode 方式输出的字符
+    Key(u16, u8),  // 虚拟键码和修饰标记
+}
+
+// 输入完成后清除按键序列中的字符
+impl Zeroize for KeyStroke {
+    fn zeroize(&mut self) {
+        *self = KeyStroke::Key(0, 0);
+    }
+}
+
+// 克隆的模拟器共享同一个中止标记
+#[derive(Clone)]
+pub struct InputSimulator {
+    delay_ms: u64,               // 字符间延迟（毫秒）
+    batch_size: usize,           // 批处理字符数量
+    batch_delay_ms: u64,         // 批次间延迟（毫秒）
+    newline: NewlinePolicy,      // 换行策略
+    tab: TabPolicy,              // 制表符策略
+    backend: Backend,            // 输出后端
+    transforms: Vec<Transform>,  // 输入前的文本转换
+    indent: IndentPolicy,        // 编辑器自动缩进的补偿方式
+    auto_close: String,          // 编辑器会自动补全闭合字符的开始字符
+    window: Option<usize>,       // 后台输入的目标窗口（HWND 不能跨线程，以整数保存）
+    abort_flag: Arc<AtomicBool>, // 中止标记，可从其他线程设置
+}
+
+impl InputSimulator {
+    pub fn new() -> Self {
+        InputSimulator {
+            delay_ms: 10,      // 默认字符延迟10毫秒
+            batch_size: 1,     // 默认单字符处理
+            batch_delay_ms: 0, // 默认无批次延迟
+            newline: NewlinePolicy::Enter,
+            tab: TabPolicy::Unicode,
+            backend: Backend::Unicode,
+            transforms: Vec::new(),
+            indent: IndentPolicy::Keep,
+            auto_close: String::new(),
+            window: None,
+            abort_flag: Arc::new(AtomicBool::new(false)),
+        }
     }
 
     // 设置自定义参数
     pub fn set_custom_params(&mut self, delay_ms: u64, batch_size: usize, batch_delay_ms: u64) {
         self.delay_ms = delay_ms;
-        self.batch_size = batch_size;
+        self.batch_size = batch_size.max(1);
         self.batch_delay_ms = batch_delay_ms;
         info!(
             "设置自定义参数: 延迟: {}ms, 批处理: {}, 批次延迟: {}ms",
@@ -85,97 +189,117 @@ impl InputSimulator {
         );
     }
 
+    // 应用配置方案
+    pub fn apply_profile(&mut self, name: &str, profile: &Profile) {
+        self.delay_ms = profile.delay_ms;
+        self.batch_size = profile.batch_size.max(1);
+        self.batch_delay_ms = profile.batch_delay_ms;
+        self.newline = profile.newline;
+        self.tab = profile.tab;
+        self.backend = profile.backend;
+        self.transforms = profile.transforms.clone();
+        self.indent = profile.indent;
+        self.auto_close = profile.auto_close.clone();
+        info!(
+            "已应用配置方案: {}, 延迟: {}ms, 批处理: {}, 批次延迟: {}ms, 换行: {:?}, 后端: {:?}",
+            name, self.delay_ms, self.batch_size, self.batch_delay_ms, self.newline, self.backend
+        );
+    }
+
     pub fn set_delay(&mut self, delay_ms: u64) {
         self.delay_ms = delay_ms;
         info!("按键延迟设置为 {} 毫秒", delay_ms);
     }
 
+    // 设置后不再使用 SendInput，而是向该窗口投递键盘消息，窗口无需位于前台
+    pub fn set_window(&mut self, hwnd: HWND) {
+        self.window = Some(hwnd as usize);
+    }
+
+    pub fn transforms(&self) -> &[Transform] {
+        &self.transforms
+    }
+
+    pub fn indent(&self) -> IndentPolicy {
+        self.indent
+    }
+
+    // 获取中止标记，设置为true后正在进行的输入会在下一批次前停止
+    pub fn abort_handle(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.abort_flag)
+    }
+
     // 优化版的模拟输入函数
     pub fn simulate_typing(&self, text: &str) -> Result<(), String> {
         if text.is_empty() {
             return Ok(());
         }
 
-        info!("开始模拟输入 {} 个字符", text.len());
+        info!("开始模拟输入 {} 个字符", text.chars().count());
+        if self.window.is_some() && self.indent == IndentPolicy::Replace {
+            warn!("后台输入无法发送 Shift+Home，行首缩进按原样输入");
+        }
+        let strokes = Zeroizing::new(self.plan(text));
+        self.send_strokes(&strokes)
+    }
+
+    // 按当前的批处理和延迟设置发送按键序列，开始前清除之前的中止请求
+    pub fn send_strokes(&self, strokes: &[KeyStroke]) -> Result<(), String> {
+        self.abort_flag.store(false, Ordering::SeqCst);
+        self.send(strokes, true)
+    }
+
+    // 同一次输入的后续步骤：保留之前的中止请求，已中止时不再发送
+    pub fn continue_strokes(&self, strokes: &[KeyStroke]) -> Result<(), String> {
+        self.send(strokes, true)
+    }
+
+    // 必须发送的收尾按键（例如取消选中），不检查也不清除中止请求
+    pub fn finish_strokes(&self, strokes: &[KeyStroke]) -> Result<(), String> {
+        self.send(strokes, false)
+    }
+
+    // 是否请求了中止，请求保留到下一次 send_strokes
+    pub fn aborted(&self) -> bool {
+        self.abort_flag.load(Ordering::SeqCst)
+    }
+
+    fn send(&self, strokes: &[KeyStroke], abortable: bool) -> Result<(), String> {
         let start_time = std::time::Instant::now();
+        let total_strokes = strokes.len();
+        let mut strokes_processed = 0;
 
-        let chars: Vec<char> = text.chars().collect();
-        let total_chars = chars.len();
-        let mut chars_processed = 0;
-
-        // 分批处理字符
-        for chunk in chars.chunks(self.batch_size) {
-            let mut inputs = Vec::with_capacity(chunk.len() * 2); // 每个字符需要2个事件（按下和释放）
-
-            for &c in chunk {
-                if c == '\n' || c == '\r' {
-                    // 特殊处理换行符
-                    let mut down_input: INPUT = unsafe { std::mem::zeroed() };
-                    down_input.type_ = INPUT_KEYBOARD;
-                    let ki = unsafe { down_input.u.ki_mut() };
-                    ki.wVk = VK_RETURN as WORD;
-                    ki.dwFlags = 0;
-
-                    let mut up_input: INPUT = unsafe { std::mem::zeroed() };
-                    up_input.type_ = INPUT_KEYBOARD;
-                    let ki = unsafe { up_input.u.ki_mut() };
-                    ki.wVk = VK_RETURN as WORD;
-                    ki.dwFlags = KEYEVENTF_KEYUP;
-
-                    inputs.push(down_input);
-                    inputs.push(up_input);
-                } else {
-                    // 普通字符
-                    let mut down_input: INPUT = unsafe { std::mem::zeroed() };
-                    down_input.type_ = INPUT_KEYBOARD;
-                    let ki = unsafe { down_input.u.ki_mut() };
-                    ki.wVk = 0;
-                    ki.wScan = c as WORD;
-                    ki.dwFlags = KEYEVENTF_UNICODE;
-
-                    let mut up_input: INPUT = unsafe { std::mem::zeroed() };
-                    up_input.type_ = INPUT_KEYBOARD;
-                    let ki = unsafe { up_input.u.ki_mut() };
-                    ki.wVk = 0;
-                    ki.wScan = c as WORD;
-                    ki.dwFlags = KEYEVENTF_UNICODE | KEYEVENTF_KEYUP;
-
-                    inputs.push(down_input);
-                    inputs.push(up_input);
-                }
+        // 分批处理按键
+        for chunk in strokes.chunks(self.batch_size.max(1)) {
+            if abortable && self.aborted() {
+                warn!("输入已中止，已输入 {}/{}", strokes_processed, total_strokes);
+                return Err("输入已中止".into());
             }
 
-            // 批量发送输入事件
-            let result = unsafe {
-                SendInput(
-                    inputs.len() as UINT,
-                    inputs.as_mut_ptr(),
-                    std::mem::size_of::<INPUT>() as i32,
-                )
-            };
-
-            if result != inputs.len() as UINT {
-                error!(
-                    "SendInput失败，只发送了 {} 个输入中的 {}",
-                    result,
-                    inputs.len()
-                );
-                return Err(format!("SendInput失败，结果: {}", result));
+            match self.window {
+                Some(hwnd) => {
+                    for &stroke in chunk {
+                        post_stroke(hwnd as HWND, stroke)?;
+                    }
+                }
+                None => self.send_chunk(chunk)?,
             }
 
-            chars_processed += chunk.len();
+            strokes_processed += chunk.len();
 
             // 进度反馈 - 每处理10%进度报告一次
-            if chars_processed % (total_chars / 10 + 1) == 0 || chars_processed == total_chars {
-                let progress = (chars_processed as f64 / total_chars as f64 * 100.0) as u32;
+            if strokes_processed % (total_strokes / 10 + 1) == 0
+                || strokes_processed == total_strokes
+            {
+                let progress = (strokes_processed as f64 / total_strokes as f64 * 100.0) as u32;
                 info!(
                     "输入进度: {}% ({}/{})",
-                    progress, chars_processed, total_chars
+                    progress, strokes_processed, total_strokes
                 );
             }
 
             // 批次间延迟
-            if self.batch_delay_ms > 0 && chars_processed < total_chars {
+            if self.batch_delay_ms > 0 && strokes_processed < total_strokes {
                 thread::sleep(Duration::from_millis(self.batch_delay_ms));
             }
 
@@ -187,14 +311,302 @@ impl InputSimulator {
 
         // 统计和报告
         let elapsed = start_time.elapsed();
-        let chars_per_second = total_chars as f64 / elapsed.as_secs_f64();
+        let strokes_per_second = total_strokes as f64 / elapsed.as_secs_f64();
         info!(
-            "成功模拟输入 {} 个字符，耗时 {:.2}秒，速度 {:.2}字符/秒",
-            total_chars,
+            "成功模拟输入 {} 次按键，耗时 {:.2}秒，速度 {:.2}次/秒",
+            total_strokes,
             elapsed.as_secs_f64(),
-            chars_per_second
+            strokes_per_second
         );
 
         Ok(())
     }
+
+    // 批量发送一批按键的输入事件
+    fn send_chunk(&self, chunk: &[KeyStroke]) -> Result<(), String> {
+        let mut inputs = Vec::with_capacity(chunk.len() * 2); // 每个字符至少需要2个事件（按下和释放）
+        for &stroke in chunk {
+            self.push_stroke(&mut inputs, stroke);
+        }
+
+        let result = unsafe {
+            SendInput(
+                inputs.len() as UINT,
+                inputs.as_mut_ptr(),
+                std::mem::size_of::<INPUT>() as i32,
+            )
+        };
+
+        if result != inputs.len() as UINT {
+            error!(
+                "SendInput失败，只发送了 {} 个输入中的 {}",
+                result,
+                inputs.len()
+            );
+            return Err(format!("SendInput失败，结果: {}", result));
+        }
+        Ok(())
+    }
+
+    // 估算输入所需时间，延迟的计算方式与 simulate_typing 一致
+    pub fn estimate(&self, text: &str) -> Duration {
+        let strokes = Zeroizing::new(self.plan(text)).len() as u64;
+        let batch_size = self.batch_size.max(1);
+        let batches = strokes.div_ceil(batch_size as u64);
+        let mut ms = batches.saturating_sub(1) * self.batch_delay_ms;
+        if self.delay_ms > 0 && batch_size <= 5 {
+            ms += batches * self.delay_ms;
+        }
+        Duration::from_millis(ms)
+    }
+
+    // 将文本转换为按键序列，换行符按当前策略处理（CRLF 视为一个换行）
+    pub fn plan(&self, text: &str) -> Vec<KeyStroke> {
+        // 后台输入无法传递修饰键，Shift+Home 会变成 Home，不能用来替换缩进
+        let indent = match (self.window, self.indent) {
+            (Some(_), IndentPolicy::Replace) => IndentPolicy::Keep,
+            (_, indent) => indent,
+        };
+        let mut strokes = Vec::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        let mut pairing = Pairing::default();
+
+        while let Some(c) = chars.next() {
+            let auto_closed = pairing.push(c, &self.auto_close);
+            match c {
+                '\r' | '\n' => {
+                    if c == '\r' && chars.peek() == Some(&'\n') {
+                        chars.next();
+                    }
+                    strokes.push(match self.newline {
+                        NewlinePolicy::Enter => KeyStroke::Key(VK_RETURN as u16, 0),
+                        NewlinePolicy::ShiftEnter => KeyStroke::Key(VK_RETURN as u16, MOD_SHIFT),
+                        NewlinePolicy::Space => KeyStroke::Char(' '),
+                    });
+                    if self.newline == NewlinePolicy::Space {
+                        continue;
+                    }
+
+                    // 编辑器在换行后会自动缩进
+                    match indent {
+                        IndentPolicy::Keep => {}
+                        IndentPolicy::Strip => {
+                            while matches!(chars.peek(), Some(' ' | '\t')) {
+                                chars.next();
+                            }
+                        }
+                        IndentPolicy::Replace => {
+                            // 空行不处理，避免选区为空时误删内容
+                            if !matches!(chars.peek(), None | Some('\r' | '\n')) {
+                                strokes.push(KeyStroke::Key(VK_HOME as u16, MOD_SHIFT));
+                            }
+                        }
+                    }
+                }
+                '\t' => match self.tab {
+                    TabPolicy::Key => strokes.push(KeyStroke::Key(VK_TAB as u16, 0)),
+                    TabPolicy::Spaces(n) => {
+                        strokes.extend(std::iter::repeat_n(KeyStroke::Char(' '), n))
+                    }
+                    TabPolicy::Unicode => strokes.push(KeyStroke::Unicode('\t')),
+                },
+                _ => {
+                    strokes.push(KeyStroke::Char(c));
+                    // 删除编辑器自动插入的闭合字符
+                    if auto_closed {
+                        strokes.push(KeyStroke::Key(VK_DELETE as u16, 0));
+                    }
+                }
+            }
+        }
+
+        strokes
+    }
+
+    // 根据输出后端把一次按键转换为 INPUT 事件
+    fn push_stroke(&self, inputs: &mut Vec<INPUT>, stroke: KeyStroke) {
+        match stroke {
+            KeyStroke::Key(vk, modifiers) => push_key(inputs, vk, modifiers),
+            KeyStroke::Char(c) => {
+                if self.backend == Backend::VirtualKey {
+                    if let Some((vk, modifiers)) = map_char_to_key(c) {
+                        push_key(inputs, vk, modifiers);
+                        return;
+                    }
+                }
+                push_unicode(inputs, c);
+            }
+            KeyStroke::Unicode(c) => push_unicode(inputs, c),
+        }
+    }
+}
+
+// 跟踪已输入的文本，判断编辑器是否会为开始字符自动插入闭合字符。
+// 编辑器在这一点上各不相同，无法确定时不删除：多出一个闭合字符比删掉原文更容易发现。
+// 字符串中的字符不视为自动补全；引号在单词字符之后（例如 don't）或被转义时也不会自动补全
+#[derive(Default)]
+struct Pairing {
+    prev: Option<char>,
+    escaped: bool,
+    quote: Option<char>, // 当前所在字符串的引号，换行后结束
+}
+
+impl Pairing {
+    // 记录输入的一个字符，返回编辑器是否会在它之后自动插入闭合字符
+    fn push(&mut self, c: char, auto_close: &str) -> bool {
+        let escaped = self.escaped;
+        self.escaped = c == '\\' && !escaped;
+        let prev = self.prev.replace(c);
+
+        if matches!(c, '\r' | '\n') {
+            self.quote = None;
+            return false;
+        }
+        if !matches!(c, '"' | '\'' | '`') {
+            return self.quote.is_none() && auto_close.contains(c);
+        }
+
+        match self.quote {
+            Some(quote) => {
+                if quote == c && !escaped {
+                    self.quote = None;
+                }
+                false
+            }
+            None if prev.is_some_and(|p| p.is_alphanumeric() || p == '_') => false,
+            None => {
+                self.quote = Some(c);
+                auto_close.contains(c)
+            }
+        }
+    }
+}
+
+// 使用当前键盘布局查找字符对应的虚拟键和修饰键，无法映射时返回 None
+pub fn map_char_to_key(c: char) -> Option<(u16, u8)> {
+    let mut units = [0u16; 2];
+    let encoded = c.encode_utf16(&mut units);
+    if encoded.len() != 1 {
+        return None;
+    }
+
+    let result = unsafe { VkKeyScanW(encoded[0]) };
+    if result == -1 {
+        return None;
+    }
+
+    let vk = (result as u16) & 0xFF;
+    let state = ((result as u16) >> 8) as u8;
+    let mut modifiers = 0;
+    if state & 1 != 0 {
+        modifiers |= MOD_SHIFT;
+    }
+    if state & 2 != 0 {
+        modifiers |= MOD_CONTROL;
+    }
+    if state & 4 != 0 {
+        modifiers |= MOD_ALT;
+    }
+    Some((vk, modifiers))
+}
+
+// Unicode 方式，非BMP字符需要按 UTF-16 代理对发送
+fn push_unicode(inputs: &mut Vec<INPUT>, c: char) {
+    let mut units = [0u16; 2];
+    for &unit in c.encode_utf16(&mut units).iter() {
+        inputs.push(keyboard_input(0, unit, KEYEVENTF_UNICODE));
+        inputs.push(keyboard_input(0, unit, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP));
+    }
+}
+
+// 后台输入：字符以 WM_CHAR 投递，按键以 WM_KEYDOWN / WM_KEYUP 投递，由目标程序自行转换为字符。
+// 适用于经典 Win32 编辑框（Edit、RichEdit）和传统控制台窗口；Windows Terminal、UWP、
+// 基于 Chromium 的程序、虚拟机控制台和以管理员身份运行的程序通常不接受投递的消息。
+// 修饰键状态无法随消息传递，Shift+Enter 等组合键只发送主键，输出后端设置不起作用
+fn post_stroke(hwnd: HWND, stroke: KeyStroke) -> Result<(), String> {
+    let posted = match stroke {
+        KeyStroke::Char(c) | KeyStroke::Unicode(c) => {
+            let mut units = [0u16; 2];
+            c.encode_utf16(&mut units)
+                .iter()
+                .all(|&unit| unsafe { PostMessageW(hwnd, WM_CHAR, unit as WPARAM, 1) } != 0)
+        }
+        KeyStroke::Key(vk, _) => {
+            let scan = unsafe { MapVirtualKeyW(vk as UINT, MAPVK_VK_TO_VSC) };
+            let mut down = 1 | (scan << 16); // 重复次数 1 和扫描码
+            if is_extended(vk) {
+                down |= 1 << 24;
+            }
+            let up = down | 0xC000_0000; // 之前按下、正在释放
+            unsafe {
+                PostMessageW(hwnd, WM_KEYDOWN, vk as WPARAM, down as LPARAM) != 0
+                    && PostMessageW(hwnd, WM_KEYUP, vk as WPARAM, up as LPARAM) != 0
+            }
+        }
+    };
+
+    if posted {
+        Ok(())
+    } else {
+        let code = unsafe { GetLastError() };
+        error!("无法向目标窗口投递消息，错误码: {}", code);
+        Err(format!(
+            "无法向目标窗口发送按键，窗口可能已关闭，错误码: {}",
+            code
+        ))
+    }
+}
+
+// 按下修饰键、按下并释放虚拟键、再释放修饰键
+fn push_key(inputs: &mut Vec<INPUT>, vk: u16, modifiers: u8) {
+    let modifier_keys: Vec<u16> = [
+        (MOD_CONTROL, VK_CONTROL),
+        (MOD_SHIFT, VK_SHIFT),
+        (MOD_ALT, VK_MENU),
+    ]
+    .iter()
+    .filter(|(flag, _)| modifiers & flag != 0)
+    .map(|&(_, key)| key as u16)
+    .collect();
+
+    let flags = if is_extended(vk) {
+        KEYEVENTF_EXTENDEDKEY
+    } else {
+        0
+    };
+    for &key in &modifier_keys {
+        inputs.push(keyboard_input(key, 0, 0));
+    }
+    inputs.push(keyboard_input(vk, 0, flags));
+    inputs.push(keyboard_input(vk, 0, flags | KEYEVENTF_KEYUP));
+    for &key in modifier_keys.iter().rev() {
+        inputs.push(keyboard_input(key, 0, KEYEVENTF_KEYUP));
+    }
+}
+
+// 导航键位于扩展键区，不带扩展标记时会被当作数字小键盘上的同名键，NumLock 开启时输入数字
+fn is_extended(vk: u16) -> bool {
+    matches!(
+        vk as i32,
+        VK_INSERT
+            | VK_DELETE
+            | VK_HOME
+            | VK_END
+            | VK_PRIOR
+            | VK_NEXT
+            | VK_LEFT
+            | VK_RIGHT
+            | VK_UP
+            | VK_DOWN
+    )
+}
+
+fn keyboard_input(vk: u16, scan: u16, flags: DWORD) -> INPUT {
+    let mut input: INPUT = unsafe { std::mem::zeroed() };
+    input.type_ = INPUT_KEYBOARD;
+    let ki = unsafe { input.u.ki_mut() };
+    ki.wVk = vk as WORD;
+    ki.wScan = scan as WORD;
+    ki.dwFlags = flags;
+    input
 }
@@ -1,21 +1,54 @@
 // src/main.rs
+mod cli;
+mod click;
 mod clipboard;
+mod config;
+mod history;
 mod hotkey;
 mod input_sim;
+mod preflight;
+mod richtext;
+mod session;
+mod snippets;
+mod source;
+mod template;
+mod transform;
+mod vault;
+mod verify;
+mod watcher;
+mod window;
 
+use crate::cli::{
+    Command, ConfigAction, DaemonOptions, SnippetAction, TargetOptions, TypeOptions, VaultAction,
+};
 use crate::clipboard::ClipboardManager;
-use crate::hotkey::{HotkeyManager, MOD_CONTROL, MOD_SHIFT};
+use crate::config::{Config, ConfigStore, TargetConfig, TargetMode};
+use crate::hotkey::{parse_hotkey, HotkeyManager};
 use crate::input_sim::{InputSimulator, InputSpeed};
+use crate::snippets::Snippet;
+use crate::source::TextSource;
+use crate::vault::{Vault, VaultEntry};
+use crate::watcher::ClipboardWatcher;
 use log::{error, info};
 use simple_logger::SimpleLogger;
 use std::io::{self, Write};
-use std::sync::mpsc;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use winapi::shared::windef::HWND;
+use zeroize::Zeroizing;
 
 // 虚拟键码
-const VK_V: i32 = 0x56;
+const VK_A: i32 = 0x41;
+const VK_H: i32 = 0x48;
+const VK_1: i32 = 0x31;
+const VK_9: i32 = 0x39;
+
+// 没有动作时检查配置文件是否被修改的间隔
+const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(1);
 
 enum Action {
     CaptureClipboard,
@@ -26,44 +59,690 @@ enum Action {
     CustomParams,
     Exit,
     Hotkey,
+    Slot(usize),
+    Load(TextSource),
+    ReloadConfig,
+    History,
+    ShowHistory,
+    ClipboardChanged,
+    ToggleWatch,
+    ToggleSecret,
+    Analyze,
+    Snippets,
+    Vault,
+    ArmClick,
+}
+
+// 待输入文本的缓冲区，内容在替换、清除或丢弃时清零
+#[derive(Default)]
+struct Buffer {
+    text: Option<Zeroizing<String>>,
+    loaded_at: Option<Instant>,
+    template: bool, // 内容来自片段，输入前展开模板变量
+    literal: bool,  // 内容是命令行或菜单中输入的文本，从不展开模板变量
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+impl Buffer {
+    fn set(&mut self, text: Zeroizing<String>) {
+        self.text = Some(text);
+        self.loaded_at = Some(Instant::now());
+        self.template = false;
+        self.literal = false;
+    }
+
+    // 载入片段，保留未展开的文本，每次输入时重新展开
+    fn set_template(&mut self, text: Zeroizing<String>) {
+        self.set(text);
+        self.template = true;
+    }
+
+    fn set_literal(&mut self, text: Zeroizing<String>) {
+        self.set(text);
+        self.literal = true;
+    }
+
+    // 按配置判断输入前是否展开模板变量
+    fn expands_templates(&self, config: &Config) -> bool {
+        expands_templates(config, self.template, self.literal)
+    }
+
+    fn get(&self) -> Option<Zeroizing<String>> {
+        self.text.clone()
+    }
+
+    fn clear(&mut self) {
+        self.text = None;
+        self.loaded_at = None;
+        self.template = false;
+        self.literal = false;
+    }
+
+    // 缓冲区内容保留 keep 时长后的到期时间
+    fn expires_at(&self, keep: Duration) -> Option<Instant> {
+        self.loaded_at.map(|loaded_at| loaded_at + keep)
+    }
+}
+
+// 菜单线程发出的请求在处理完成时通知菜单线程，
+// 避免菜单与主循环同时读取标准输入
+struct Request {
+    action: Action,
+    completion: Option<Completion>,
+}
+
+struct Completion(mpsc::Sender<()>);
+
+impl Drop for Completion {
+    fn drop(&mut self) {
+        let _ = self.0.send(());
+    }
+}
+
+impl From<Action> for Request {
+    fn from(action: Action) -> Self {
+        Request {
+            action,
+            completion: None,
+        }
+    }
+}
+
+fn main() {
     // 初始化日志记录
     SimpleLogger::new()
         .with_level(log::LevelFilter::Info)
         .init()
         .expect("无法初始化日志记录器");
 
+    let invocation = match cli::parse(std::env::args().skip(1)) {
+        Ok(invocation) => invocation,
+        Err(e) => {
+            eprintln!("错误 (Error): {}\n\n{}", e, cli::USAGE);
+            process::exit(cli::EXIT_USAGE);
+        }
+    };
+
+    let config_path = invocation.config_path.or_else(config::default_config_path);
+
+    let code = match invocation.command {
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            cli::EXIT_OK
+        }
+        Command::Config(action) => run_config(action, config_path),
+        command => {
+            let store = match ConfigStore::open(config_path) {
+                Ok(store) => store,
+                Err(e) => {
+                    eprintln!("错误 (Error): {}", e);
+                    process::exit(cli::EXIT_FAILURE);
+                }
+            };
+
+            match command {
+                Command::Type(options) => run_type(&store, options),
+                Command::Analyze(options) => run_analyze(&store, options),
+                Command::History => run_history(&store),
+                Command::Snippet(action) => run_snippet(&store, action),
+                Command::Vault(action) => run_vault(&store, action),
+                Command::Daemon(options) => exit_code(run_event_loop(store, options, false)),
+                _ => exit_code(run_event_loop(
+                    store,
+                    DaemonOptions {
+                        hotkey: None,
+                        profile: None,
+                        countdown: None,
+                        target: TargetOptions::default(),
+                        watch: false,
+                        secret: false,
+                    },
+                    true,
+                )),
+            }
+        }
+    };
+
+    process::exit(code);
+}
+
+fn run_config(action: ConfigAction, path: Option<std::path::PathBuf>) -> i32 {
+    let path = match path {
+        Some(path) => path,
+        None => {
+            eprintln!("错误 (Error): 无法确定配置文件位置，请使用 --config 指定");
+            return cli::EXIT_FAILURE;
+        }
+    };
+
+    match action {
+        ConfigAction::Path => {
+            println!("{}", path.display());
+            cli::EXIT_OK
+        }
+        ConfigAction::Init => match config::write_template(&path) {
+            Ok(_) => {
+                println!("已写入配置文件 (Config written): {}", path.display());
+                cli::EXIT_OK
+            }
+            Err(e) => {
+                eprintln!("错误 (Error): {}", e);
+                cli::EXIT_FAILURE
+            }
+        },
+    }
+}
+
+fn exit_code(result: Result<(), Box<dyn std::error::Error>>) -> i32 {
+    match result {
+        Ok(_) => cli::EXIT_OK,
+        Err(e) => {
+            eprintln!("错误 (Error): {}", e);
+            cli::EXIT_FAILURE
+        }
+    }
+}
+
+// 倒计时，给用户时间切换到目标窗口
+fn countdown(seconds: u64) {
+    for i in (1..=seconds).rev() {
+        println!("{}...", i);
+        thread::sleep(Duration::from_secs(1));
+    }
+}
+
+// 输入前切换到目标窗口的方式
+struct Focus {
+    target: TargetConfig,
+    countdown_secs: u64,
+}
+
+impl Focus {
+    fn new(config: &Config, options: &TargetOptions, countdown_secs: u64) -> Self {
+        // 命令行指定的目标窗口代替配置文件中的条件
+        let mut target = config.target.clone();
+        if options.title.is_some() || options.exe.is_some() {
+            target.title = options.title.clone();
+            target.exe = options.exe.clone();
+            target.class = None;
+        }
+        if options.wait {
+            target.mode = TargetMode::Wait;
+        }
+        if options.click {
+            target.mode = TargetMode::Click;
+        }
+        if options.background {
+            target.background = true;
+        }
+        if let Some(timeout_secs) = options.timeout_secs {
+            target.timeout_secs = timeout_secs;
+        }
+        Focus {
+            target,
+            countdown_secs,
+        }
+    }
+
+    // 只等待下一次点击，用于菜单中的点击输入
+    fn armed(&self) -> Focus {
+        let mut target = self.target.clone();
+        target.mode = TargetMode::Click;
+        Focus {
+            target,
+            countdown_secs: self.countdown_secs,
+        }
+    }
+
+    // 设置了目标窗口时激活或等待它，点击模式下等待点击，否则倒计时让用户手动切换。
+    // 后台输入时不切换前台窗口，返回接收输入的控件
+    fn run(&self) -> Result<Option<HWND>, String> {
+        if self.target.mode != TargetMode::Click && !self.target.is_set() {
+            if self.target.background {
+                return Err("后台输入需要设置目标窗口或使用点击模式 (Background typing needs a target window or click mode)".into());
+            }
+            if self.countdown_secs > 0 {
+                println!(
+                    "请在{}秒内切换到目标窗口... (Please switch to target window within {} seconds...)",
+                    self.countdown_secs, self.countdown_secs
+                );
+                countdown(self.countdown_secs);
+            }
+            return Ok(None);
+        }
+
+        match (self.target.mode, self.target.background) {
+            (TargetMode::Click, _) => println!(
+                "请在{}秒内点击要输入的位置，右键取消... (Click where the text should go within {} seconds, right-click to cancel...)",
+                self.target.timeout_secs, self.target.timeout_secs
+            ),
+            (_, true) => println!("正在查找目标窗口... (Looking for the target window...)"),
+            (TargetMode::Activate, false) => {
+                println!("正在激活目标窗口... (Activating the target window...)")
+            }
+            (TargetMode::Wait, false) => println!(
+                "请切换到目标窗口，最多等待{}秒... (Switch to the target window, waiting up to {} seconds...)",
+                self.target.timeout_secs, self.target.timeout_secs
+            ),
+        }
+
+        if self.target.background {
+            let (hwnd, window) = session::find_target(&self.target)?;
+            println!(
+                "将在后台输入到 (Typing in the background into): {}",
+                window.title
+            );
+            return Ok(Some(hwnd));
+        }
+
+        let window = session::focus_target(&self.target)?;
+        println!(
+            "目标窗口已在前台 (Target window is in the foreground): {}",
+            window.title
+        );
+        Ok(None)
+    }
+}
+
+// 列出持久化的剪贴板历史
+fn run_history(store: &ConfigStore) -> i32 {
+    if !store.config().history.persist {
+        eprintln!(
+            "错误 (Error): 剪贴板历史未开启持久化，请在配置文件中设置 history.persist = true"
+        );
+        return cli::EXIT_FAILURE;
+    }
+
+    let history = config::open_history(store.config(), store.path());
+    history.print();
+    cli::EXIT_OK
+}
+
+// 提示输入口令并解锁密码库
+fn unlock_vault(store: &ConfigStore) -> Result<Vault, String> {
+    let path = vault::vault_path(store.path())?;
+    let passphrase = vault::read_line("密码库口令 (Vault passphrase)", true)?;
+    Vault::open(path, &passphrase)
+}
+
+// 管理加密密码库
+fn run_vault(store: &ConfigStore, action: VaultAction) -> i32 {
+    let result = match action {
+        VaultAction::Init => vault::vault_path(store.path()).and_then(|path| {
+            let passphrase = vault::read_line("设置口令 (New passphrase)", true)?;
+            let confirm = vault::read_line("再次输入口令 (Repeat passphrase)", true)?;
+            if passphrase.is_empty() {
+                return Err("口令不能为空".into());
+            }
+            if *passphrase != *confirm {
+                return Err("两次输入的口令不一致".into());
+            }
+            let vault = Vault::create(path, &passphrase)?;
+            println!("已创建密码库 (Vault created)");
+            drop(vault);
+            Ok(())
+        }),
+        VaultAction::List => unlock_vault(store).map(|vault| vault.print()),
+        VaultAction::Remove(name) => unlock_vault(store).and_then(|mut vault| {
+            vault.remove(&name)?;
+            println!("已删除条目 (Entry deleted): {}", name);
+            Ok(())
+        }),
+        VaultAction::Add {
+            name,
+            fields,
+            sequence,
+        } => unlock_vault(store).and_then(|mut vault| {
+            let mut entry = VaultEntry::new();
+            entry.insert(
+                "username".into(),
+                vault::read_line("用户名 (Username)", false)?,
+            );
+            entry.insert(
+                "password".into(),
+                vault::read_line("密码 (Password)", true)?,
+            );
+            for field in fields {
+                let value = vault::read_line(&field, true)?;
+                entry.insert(field.to_lowercase(), value);
+            }
+            if let Some(sequence) = sequence {
+                entry.insert("sequence".into(), Zeroizing::new(sequence));
+            }
+
+            if vault.set(&name, entry)? {
+                println!("已替换条目 (Entry replaced): {}", name);
+            } else {
+                println!("已保存条目 (Entry saved): {}", name);
+            }
+            Ok(())
+        }),
+        VaultAction::Type {
+            name,
+            sequence,
+            profile,
+            countdown: countdown_secs,
+            target,
+        } => {
+            let config = store.config();
+            let profile_name = profile.unwrap_or_else(|| config.default_profile.clone());
+            match config.profile(&profile_name) {
+                Some(profile) => unlock_vault(store).and_then(|vault| {
+                    let strokes = vault.strokes(&name, sequence.as_deref())?;
+                    drop(vault);
+
+                    let mut simulator = InputSimulator::new();
+                    simulator.apply_profile(&profile_name, &profile);
+                    let countdown_secs =
+                        countdown_secs.unwrap_or_else(|| config.countdown_for(&profile_name));
+                    let window = Focus::new(config, &target, countdown_secs).run()?;
+                    session::type_strokes(config, &simulator, window, &strokes)?;
+                    println!("自动输入完成 (Auto-type completed)");
+                    Ok(())
+                }),
+                None => {
+                    eprintln!("错误 (Error): 配置方案不存在: {}", profile_name);
+                    return cli::EXIT_USAGE;
+                }
+            }
+        }
+    };
+
+    match result {
+        Ok(_) => cli::EXIT_OK,
+        Err(e) => {
+            eprintln!("错误 (Error): {}", e);
+            cli::EXIT_FAILURE
+        }
+    }
+}
+
+// 管理片段库
+fn run_snippet(store: &ConfigStore, action: SnippetAction) -> i32 {
+    let mut snippets = config::open_snippets(store.path());
+    let result = match action {
+        SnippetAction::List(query) => {
+            snippets.print(query.as_deref());
+            Ok(())
+        }
+        SnippetAction::Remove(name) => snippets.remove(&name).map(|_| {
+            println!("已删除片段 (Snippet deleted): {}", name);
+        }),
+        SnippetAction::Add {
+            name,
+            tags,
+            slot,
+            source,
+        } => {
+            let mut cm = config::open_clipboard(store.config(), store.path());
+            cm.set_secret(store.config().secret.enabled);
+            source
+                .read(&mut cm, &snippets)
+                .and_then(|text| snippets.add(&name, Snippet { text, tags, slot }))
+                .map(|replaced| {
+                    if replaced {
+                        println!("已替换片段 (Snippet replaced): {}", name);
+                    } else {
+                        println!("已保存片段 (Snippet saved): {}", name);
+                    }
+                })
+        }
+    };
+
+    match result {
+        Ok(_) => cli::EXIT_OK,
+        Err(e) => {
+            eprintln!("错误 (Error): {}", e);
+            cli::EXIT_FAILURE
+        }
+    }
+}
+
+// 非交互式的一次性输入
+fn run_type(store: &ConfigStore, options: TypeOptions) -> i32 {
+    let mut config = store.config().clone();
+    if options.verify {
+        config.verify.enabled = true;
+    }
+    let config = &config;
+    let profile_name = options
+        .profile
+        .unwrap_or_else(|| config.default_profile.clone());
+    let profile = match config.profile(&profile_name) {
+        Some(profile) => profile,
+        None => {
+            eprintln!("错误 (Error): 配置方案不存在: {}", profile_name);
+            return cli::EXIT_USAGE;
+        }
+    };
+    let countdown_secs = options
+        .countdown
+        .unwrap_or_else(|| config.countdown_for(&profile_name));
+    let focus = Focus::new(config, &options.target, countdown_secs);
+
+    let mut simulator = InputSimulator::new();
+    simulator.apply_profile(&profile_name, &profile);
+    if let Some(delay_ms) = options.delay_ms {
+        simulator.set_delay(delay_ms);
+    }
+
+    let mut cm = config::open_clipboard(config, store.path());
+    cm.set_secret(options.secret || config.secret.enabled);
+    let snippets = config::open_snippets(store.path());
+    let text = match options.source.read(&mut cm, &snippets) {
+        Ok(text) => Zeroizing::new(text),
+        Err(e) => {
+            eprintln!("错误 (Error): {}", e);
+            return cli::EXIT_FAILURE;
+        }
+    };
+
+    // 在倒计时前展开模板变量，以便在控制台询问缺少的值
+    let snippet = matches!(options.source, TextSource::Snippet(_));
+    let literal = matches!(options.source, TextSource::Literal(_));
+    let text = if options.raw || !expands_templates(config, snippet, literal) {
+        text
+    } else {
+        match template::expand(&text, &cm, true) {
+            Ok(text) => text,
+            Err(e) => {
+                eprintln!("错误 (Error): {}", e);
+                return cli::EXIT_FAILURE;
+            }
+        }
+    };
+
+    let result = focus
+        .run()
+        .and_then(|window| session::type_text(config, &simulator, &mut cm, window, &text));
+    if let Err(e) = cm.restore_after_typing() {
+        eprintln!("错误 (Error): {}", e);
+    }
+
+    match result {
+        Ok(_) => {
+            println!("模拟输入完成 (Typing simulation completed)");
+            cli::EXIT_OK
+        }
+        Err(e) => {
+            eprintln!("错误 (Error): {}", e);
+            cli::EXIT_FAILURE
+        }
+    }
+}
+
+// 非交互式的预检分析，未通过时返回失败
+fn run_analyze(store: &ConfigStore, options: TypeOptions) -> i32 {
+    let config = store.config();
+    let profile_name = options
+        .profile
+        .unwrap_or_else(|| config.default_profile.clone());
+    let profile = match config.profile(&profile_name) {
+        Some(profile) => profile,
+        None => {
+            eprintln!("错误 (Error): 配置方案不存在: {}", profile_name);
+            return cli::EXIT_USAGE;
+        }
+    };
+
+    let mut simulator = InputSimulator::new();
+    simulator.apply_profile(&profile_name, &profile);
+    if let Some(delay_ms) = options.delay_ms {
+        simulator.set_delay(delay_ms);
+    }
+
+    let secret = options.secret || config.secret.enabled;
+    let mut cm = config::open_clipboard(config, store.path());
+    cm.set_secret(secret);
+    let snippets = config::open_snippets(store.path());
+    let text = match options.source.read(&mut cm, &snippets) {
+        Ok(text) => Zeroizing::new(text),
+        Err(e) => {
+            eprintln!("错误 (Error): {}", e);
+            return cli::EXIT_FAILURE;
+        }
+    };
+
+    let report = preflight::analyze(&config.preflight, &simulator, &text);
+    println!("{}", report.describe(secret));
+    if report.blocked() {
+        cli::EXIT_FAILURE
+    } else {
+        cli::EXIT_OK
+    }
+}
+
+// 注册触发热键和前导键序列
+fn register_hotkeys(
+    manager: &mut HotkeyManager,
+    config: &Config,
+    trigger_override: Option<&str>,
+    tx: &mpsc::Sender<Request>,
+    abort_flag: Arc<AtomicBool>,
+) -> Result<(), String> {
+    let trigger = parse_hotkey(trigger_override.unwrap_or(&config.hotkeys.trigger))?;
+    let leader = parse_hotkey(&config.hotkeys.leader)?;
+
+    // 注册热键
+    let tx_trigger = tx.clone();
+    manager.register(trigger.0, trigger.1, move || {
+        let _ = tx_trigger.send(Action::Hotkey.into());
+    })?;
+
+    // 注册前导键序列：前导键之后按 1-9 / H / A
+    let tx_leader = tx.clone();
+    let mut follow_keys: Vec<i32> = (VK_1..=VK_9).collect();
+    follow_keys.push(VK_H);
+    follow_keys.push(VK_A);
+    manager.register_sequence(
+        leader.0,
+        leader.1,
+        &follow_keys,
+        Duration::from_millis(config.hotkeys.leader_timeout_ms),
+        move |key| match key {
+            // 中止需要直接设置标记，因为主循环此时可能正忙于输入
+            VK_A => abort_flag.store(true, Ordering::SeqCst),
+            VK_H => {
+                let _ = tx_leader.send(Action::ShowHistory.into());
+            }
+            k => {
+                let _ = tx_leader.send(Action::Slot((k - VK_1 + 1) as usize).into());
+            }
+        },
+    )
+}
+
+// 热键与菜单共用的事件循环；interactive 为 false 时不显示菜单（daemon 模式）
+fn run_event_loop(
+    mut store: ConfigStore,
+    options: DaemonOptions,
+    interactive: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
     info!("启动粘贴绕过应用程序（命令行版本）");
 
+    let mut active_profile = options
+        .profile
+        .unwrap_or_else(|| store.config().default_profile.clone());
+    let profile = store
+        .config()
+        .profile(&active_profile)
+        .ok_or_else(|| format!("配置方案不存在: {}", active_profile))?;
+
     // 创建共享的状态
-    let clipboard_manager = Arc::new(Mutex::new(ClipboardManager::new()));
-    let input_simulator = Arc::new(Mutex::new(InputSimulator::with_delay(10))); // 默认10毫秒延迟
-    let buffer = Arc::new(Mutex::new(None::<String>));
+    let clipboard_manager = Arc::new(Mutex::new(config::open_clipboard(
+        store.config(),
+        store.path(),
+    )));
+    let input_simulator = Arc::new(Mutex::new(InputSimulator::new()));
+    input_simulator
+        .lock()
+        .unwrap()
+        .apply_profile(&active_profile, &profile);
+    let buffer = Arc::new(Mutex::new(Buffer::default()));
+
+    // 敏感内容模式：不记录历史、不显示内容，输入后或超时后清除缓冲区
+    let mut secret = options.secret || store.config().secret.enabled;
+    clipboard_manager.lock().unwrap().set_secret(secret);
     let mut hotkey_manager = HotkeyManager::new();
 
     // 创建一个通道用于动作通知
     let (tx, rx) = mpsc::channel();
-    let tx_clone = tx.clone();
-
-    // 注册热键
-    hotkey_manager.register(MOD_CONTROL | MOD_SHIFT, VK_V, move || {
-        let _ = tx_clone.send(Action::Hotkey);
-    })?;
+    let abort_flag = input_simulator.lock().unwrap().abort_handle();
+    register_hotkeys(
+        &mut hotkey_manager,
+        store.config(),
+        options.hotkey.as_deref(),
+        &tx,
+        abort_flag.clone(),
+    )?;
 
     println!("粘贴绕过工具 (Paste Bypass Tool) - 命令行版本 (Command Line Version)");
     println!("=========================");
-    println!("按 Ctrl+Shift+V 触发粘贴绕过 (Press Ctrl+Shift+V to trigger paste bypass)");
+    let trigger_name = options
+        .hotkey
+        .clone()
+        .unwrap_or_else(|| store.config().hotkeys.trigger.clone());
+    let leader_name = store.config().hotkeys.leader.clone();
+    println!(
+        "按 {} 触发粘贴绕过 (Press {} to trigger paste bypass)",
+        trigger_name, trigger_name
+    );
+    println!("按 {} 后再按 1-9 / H / A 输入第N条历史、查看历史或中止输入 (Press {}, then 1-9 / H / A to type history entry N, list history or abort)", leader_name, leader_name);
+    if let Some(path) = store.path() {
+        println!("配置文件 (Config file): {}", path.display());
+    }
+    println!("当前配置方案 (Active profile): {}", active_profile);
+    if secret {
+        println!("敏感内容模式已开启 (Secret mode enabled)");
+    }
+
+    // 自动捕获剪贴板变化
+    let mut watcher = None;
+    if options.watch || store.config().watch.enabled {
+        set_watching(&mut watcher, true, &tx);
+    }
     println!("按 Ctrl+C 退出程序 (Press Ctrl+C to exit the program)");
-    println!("");
+    println!();
 
     // 创建用户输入线程
     let tx_user = tx.clone();
-    let running = Arc::new(Mutex::new(true));
+    // daemon 模式下运行标记为false，菜单循环不会启动
+    let running = Arc::new(Mutex::new(interactive));
     let running_clone = running.clone();
 
     thread::spawn(move || {
+        let (done_tx, done_rx) = mpsc::channel();
+
+        // 发送请求并等待主循环处理完成
+        let send = |action: Action| {
+            let request = Request {
+                action,
+                completion: Some(Completion(done_tx.clone())),
+            };
+            if tx_user.send(request).is_ok() {
+                let _ = done_rx.recv();
+            }
+        };
+
         while *running_clone.lock().unwrap() {
             println!("\n选项 (Options):");
             println!("1. 捕获剪贴板 (Capture Clipboard)");
@@ -73,8 +752,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("5. 设置速度模式 (Set Speed Mode)");
             println!("6. 自定义输入参数 (Custom Input Parameters)");
             println!("7. 退出 (Exit)");
+            println!("8. 从文件读取 (Load From File)");
+            println!("9. 输入文本 (Enter Text)");
+            println!("10. 重新加载配置 (Reload Config)");
+            println!("11. 剪贴板历史 (Clipboard History)");
+            println!("12. 自动捕获开关 (Toggle Auto Capture)");
+            println!("13. 敏感内容模式开关 (Toggle Secret Mode)");
+            println!("14. 预检分析 (Pre-flight Analysis)");
+            println!("15. 片段库 (Snippet Library)");
+            println!("16. 密码库自动输入 (Vault Auto-Type)");
+            println!("17. 点击目标后输入 (Arm and Click)");
 
-            print!("请选择 (Please select) (1-7): ");
+            print!("请选择 (Please select) (1-17): ");
             io::stdout().flush().unwrap();
 
             let mut choice = String::new();
@@ -84,32 +773,84 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             match choice.trim() {
                 "1" => {
-                    let _ = tx_user.send(Action::CaptureClipboard);
+                    send(Action::CaptureClipboard);
                 }
                 "2" => {
-                    let _ = tx_user.send(Action::SimulateTyping);
+                    send(Action::SimulateTyping);
                 }
                 "3" => {
-                    let _ = tx_user.send(Action::ShowBuffer);
+                    send(Action::ShowBuffer);
                 }
                 "4" => {
-                    let _ = tx_user.send(Action::SetDelay);
+                    send(Action::SetDelay);
                 }
                 "5" => {
-                    let _ = tx_user.send(Action::SetSpeedMode);
+                    send(Action::SetSpeedMode);
                 }
                 "6" => {
-                    let _ = tx_user.send(Action::CustomParams);
+                    send(Action::CustomParams);
                 }
                 "7" => {
-                    let _ = tx_user.send(Action::Exit);
+                    send(Action::Exit);
                     break;
                 }
-                _ => println!("无效的选择，请输入1-7 (Invalid choice, please enter 1-7)"),
+                "8" => {
+                    print!("输入文件路径 (Enter file path): ");
+                    io::stdout().flush().unwrap();
+                    let mut path = String::new();
+                    if io::stdin().read_line(&mut path).is_ok() && !path.trim().is_empty() {
+                        let path = path.trim().trim_matches('"');
+                        send(Action::Load(TextSource::File(path.into())));
+                    }
+                }
+                "9" => {
+                    println!("输入文本，单独一行 . 结束 (Enter text, finish with a line containing only .):");
+                    let mut lines = Vec::new();
+                    let mut line = String::new();
+                    while let Ok(n) = io::stdin().read_line(&mut line) {
+                        if n == 0 || line.trim_end_matches(['\r', '\n']) == "." {
+                            break;
+                        }
+                        lines.push(line.trim_end_matches(['\r', '\n']).to_owned());
+                        line.clear();
+                    }
+                    send(Action::Load(TextSource::Literal(lines.join("\n"))));
+                }
+                "10" => {
+                    send(Action::ReloadConfig);
+                }
+                "11" => {
+                    send(Action::History);
+                }
+                "12" => {
+                    send(Action::ToggleWatch);
+                }
+                "13" => {
+                    send(Action::ToggleSecret);
+                }
+                "14" => {
+                    send(Action::Analyze);
+                }
+                "15" => {
+                    send(Action::Snippets);
+                }
+                "16" => {
+                    send(Action::Vault);
+                }
+                "17" => {
+                    send(Action::ArmClick);
+                }
+                _ => println!("无效的选择，请输入1-17 (Invalid choice, please enter 1-17)"),
             }
         }
     });
 
+    // 记录当前热键设置，配置变化时重新注册
+    let mut hotkeys = store.config().hotkeys.clone();
+    let mut overrides = Overrides::default();
+    let mut watch_configured = store.config().watch.enabled;
+    let mut secret_configured = store.config().secret.enabled;
+
     // 克隆共享数据用于主线程
     let clipboard_manager_clone = clipboard_manager.clone();
     let input_simulator_clone = input_simulator.clone();
@@ -117,51 +858,151 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // 主事件循环
     loop {
-        match rx.recv() {
-            Ok(action) => {
+        // 敏感内容模式下，缓冲区内容到期后清除
+        let clear_after = store.config().secret.clear_after_secs;
+        let deadline = if secret && clear_after > 0 {
+            buffer_clone
+                .lock()
+                .unwrap()
+                .expires_at(Duration::from_secs(clear_after))
+        } else {
+            None
+        };
+        let poll_at = Instant::now() + CONFIG_POLL_INTERVAL;
+        let wake_at = deadline.map_or(poll_at, |deadline| deadline.min(poll_at));
+        let received = rx.recv_timeout(wake_at.saturating_duration_since(Instant::now()));
+
+        // 配置文件被修改时自动重新加载，没有动作时也按间隔检查
+        let reload = matches!(
+            &received,
+            Ok(Request {
+                action: Action::ReloadConfig,
+                ..
+            })
+        );
+        let reloaded = if reload {
+            match store.reload() {
+                Ok(_) => true,
+                Err(e) => {
+                    println!("错误 (Error): {}", e);
+                    false
+                }
+            }
+        } else {
+            store.reload_if_changed()
+        };
+        if reloaded {
+            // 配置文件中的开关变化时同步自动捕获状态
+            let watch_enabled = store.config().watch.enabled;
+            if watch_enabled != watch_configured {
+                set_watching(&mut watcher, watch_enabled, &tx);
+                watch_configured = watch_enabled;
+            }
+            let secret_enabled = store.config().secret.enabled;
+            if secret_enabled != secret_configured {
+                secret = secret_enabled;
+                secret_configured = secret_enabled;
+                clipboard_manager_clone.lock().unwrap().set_secret(secret);
+            }
+            let mut cm = clipboard_manager_clone.lock().unwrap();
+            cm.history_mut()
+                .set_capacity(store.config().history.capacity);
+            cm.set_settings(store.config().clipboard.clone());
+            drop(cm); // 释放锁
+            apply_reloaded_config(
+                &store,
+                &mut active_profile,
+                &overrides,
+                &input_simulator_clone,
+                &mut hotkey_manager,
+                options.hotkey.as_deref(),
+                &tx,
+                &abort_flag,
+                &mut hotkeys,
+            );
+            if !reload {
+                println!("\n配置文件已修改，已重新加载 (Config file changed, reloaded)");
+            }
+        }
+
+        match received {
+            Ok(Request { action, completion }) => {
+                // 菜单线程在等待请求完成，或没有菜单（daemon 模式）时，才能在控制台询问；
+                // 热键触发时菜单线程正在读取标准输入，回答会被当作菜单选项
+                let console = completion.is_some() || !interactive;
+
+                let countdown_secs = options
+                    .countdown
+                    .unwrap_or_else(|| store.config().countdown_for(&active_profile));
+                let focus = Focus::new(store.config(), &options.target, countdown_secs);
+
                 match action {
                     Action::CaptureClipboard => {
                         let mut cm = clipboard_manager_clone.lock().unwrap();
                         match cm.capture_clipboard() {
                             Ok(_) => {
-                                let text = cm.get_buffer().cloned();
+                                let text = cm.get_buffer().cloned().map(Zeroizing::new);
                                 drop(cm); // 释放锁
 
                                 if let Some(content) = text {
-                                    *buffer_clone.lock().unwrap() = Some(content);
+                                    buffer_clone.lock().unwrap().set(content);
                                     println!("已捕获剪贴板内容 (Clipboard content captured)");
                                 }
                             }
                             Err(e) => println!("错误 (Error): {}", e),
                         }
                     }
-                    Action::SimulateTyping => {
-                        let text = buffer_clone.lock().unwrap().clone();
-                        if let Some(text) = text {
-                            println!(
-                                "将在3秒后开始模拟输入... (Starting simulation in 3 seconds...)"
-                            );
-                            for i in (1..=3).rev() {
-                                println!("{}...", i);
-                                thread::sleep(Duration::from_secs(1));
-                            }
-
-                            let sim = input_simulator_clone.lock().unwrap();
-                            match sim.simulate_typing(&text) {
-                                Ok(_) => println!("模拟输入完成 (Typing simulation completed)"),
-                                Err(e) => println!("错误 (Error): {}", e),
-                            }
-                        } else {
-                            println!("缓冲区为空，请先捕获剪贴板 (Buffer is empty, please capture clipboard first)");
-                        }
-                    }
+                    Action::SimulateTyping => type_buffer(
+                        store.config(),
+                        &input_simulator_clone,
+                        &clipboard_manager_clone,
+                        &buffer_clone,
+                        &focus,
+                        secret,
+                        console,
+                    ),
+                    Action::ArmClick => type_buffer(
+                        store.config(),
+                        &input_simulator_clone,
+                        &clipboard_manager_clone,
+                        &buffer_clone,
+                        &focus.armed(),
+                        secret,
+                        console,
+                    ),
                     Action::ShowBuffer => {
-                        let text = buffer_clone.lock().unwrap().clone();
+                        let text = buffer_clone.lock().unwrap().get();
                         if let Some(text) = text {
+                            if secret {
+                                let len = text.chars().count();
+                                println!(
+                                    "当前缓冲区内容已隐藏，共 {} 个字符 (Buffer content hidden, {} characters)",
+                                    len, len
+                                );
+                                continue;
+                            }
                             println!("当前缓冲区内容 (Current buffer content):");
                             println!("----------------");
-                            println!("{}", text);
+                            println!("{}", text.as_str());
                             println!("----------------");
+
+                            // 预览当前方案的文本转换结果
+                            let sim = input_simulator_clone.lock().unwrap();
+                            let transformed = transform::apply_all(sim.transforms(), &text);
+                            if *transformed != *text {
+                                println!("转换后将输入的内容 (Text after transforms):");
+                                println!("----------------");
+                                println!("{}", transformed.as_str());
+                                println!("----------------");
+                            }
+                            let lost = transform::unrepresentable(sim.transforms(), &text);
+                            if !lost.is_empty() {
+                                let lost: String = lost.into_iter().collect();
+                                println!(
+                                    "以下字符无法用ASCII表示，将输入为 '?' (Characters that cannot be represented in ASCII will be typed as '?'): {}",
+                                    lost
+                                );
+                            }
                         } else {
                             println!("缓冲区为空 (Buffer is empty)");
                         }
@@ -178,6 +1019,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         if let Ok(delay_ms) = delay.trim().parse::<u64>() {
                             let mut sim = input_simulator_clone.lock().unwrap();
                             sim.set_delay(delay_ms);
+                            overrides.delay_ms = Some(delay_ms);
                             println!(
                                 "按键延迟已设置为 {} 毫秒 (Key delay set to {} ms)",
                                 delay_ms, delay_ms
@@ -197,7 +1039,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                             "4. 极速 (最快，但可能被检测) (Turbo - Fastest, but may be detected)"
                         );
 
-                        print!("请选择 (Please select) (1-4): ");
+                        // 配置文件中的其他方案排在内置方案之后
+                        let names = store.config().profile_names();
+                        for (i, name) in names.iter().enumerate().skip(InputSpeed::ALL.len()) {
+                            println!("{}. {}", i + 1, name);
+                        }
+
+                        print!("请选择 (Please select) (1-{}): ", names.len());
                         io::stdout().flush().unwrap();
 
                         let mut mode_choice = String::new();
@@ -205,25 +1053,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                             continue;
                         }
 
-                        let mut simulator = input_simulator_clone.lock().unwrap();
-                        match mode_choice.trim() {
-                            "1" => {
-                                simulator.set_speed_mode(InputSpeed::Slow);
-                                println!("已设置为慢速模式 (Set to slow mode)");
-                            }
-                            "2" => {
-                                simulator.set_speed_mode(InputSpeed::Normal);
-                                println!("已设置为正常模式 (Set to normal mode)");
-                            }
-                            "3" => {
-                                simulator.set_speed_mode(InputSpeed::Fast);
-                                println!("已设置为快速模式 (Set to fast mode)");
+                        let chosen = mode_choice
+                            .trim()
+                            .parse::<usize>()
+                            .ok()
+                            .and_then(|i| names.get(i.wrapping_sub(1)));
+                        let name = match chosen {
+                            Some(name) => name.clone(),
+                            None => {
+                                println!("无效选择，使用默认正常模式 (Invalid choice, using default normal mode)");
+                                InputSpeed::Normal.name().to_string()
                             }
-                            "4" => {
-                                simulator.set_speed_mode(InputSpeed::Turbo);
-                                println!("已设置为极速模式 (Set to turbo mode)");
-                            }
-                            _ => println!("无效选择，使用默认正常模式 (Invalid choice, using default normal mode)"),
+                        };
+
+                        if let Some(profile) = store.config().profile(&name) {
+                            input_simulator_clone
+                                .lock()
+                                .unwrap()
+                                .apply_profile(&name, &profile);
+                            println!("已设置为 {} 模式 (Set to {} mode)", name, name);
+                            active_profile = name;
+                            overrides = Overrides::default();
                         }
                     }
                     Action::CustomParams => {
@@ -260,6 +1110,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
                         let mut simulator = input_simulator_clone.lock().unwrap();
                         simulator.set_custom_params(delay_ms, batch_size, batch_delay_ms);
+                        overrides = Overrides {
+                            delay_ms: None,
+                            custom: Some((delay_ms, batch_size, batch_delay_ms)),
+                        };
                         println!(
                             "已设置自定义参数: 字符延迟={}ms, 批大小={}, 批次延迟={}ms (Custom parameters set: char delay={}ms, batch size={}, batch delay={}ms)",
                             delay_ms, batch_size, batch_delay_ms, delay_ms, batch_size, batch_delay_ms
@@ -278,23 +1132,26 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         let mut cm = clipboard_manager_clone.lock().unwrap();
                         match cm.capture_clipboard() {
                             Ok(_) => {
-                                let text = cm.get_buffer().cloned();
+                                let text = cm.get_buffer().cloned().map(Zeroizing::new);
                                 drop(cm); // 释放锁
 
                                 if let Some(content) = text {
-                                    *buffer_clone.lock().unwrap() = Some(content.clone());
+                                    buffer_clone.lock().unwrap().set(content.clone());
                                     println!("已捕获剪贴板内容 (Clipboard content captured)");
 
-                                    // 短暂延迟，让用户有时间切换窗口
-                                    println!("请在3秒内切换到目标窗口... (Please switch to target window within 3 seconds...)");
-                                    for i in (1..=3).rev() {
-                                        println!("{}...", i);
-                                        thread::sleep(Duration::from_secs(1));
-                                    }
-
-                                    // 执行模拟输入
-                                    let sim = input_simulator_clone.lock().unwrap();
-                                    match sim.simulate_typing(&content) {
+                                    // 切换到目标窗口后执行模拟输入
+                                    let result = focus.run().and_then(|window| {
+                                        let sim = input_simulator_clone.lock().unwrap();
+                                        let mut cm = clipboard_manager_clone.lock().unwrap();
+                                        session::type_text(
+                                            store.config(),
+                                            &sim,
+                                            &mut cm,
+                                            window,
+                                            &content,
+                                        )
+                                    });
+                                    match result {
                                         Ok(_) => {
                                             println!("模拟输入完成 (Typing simulation completed)")
                                         }
@@ -303,6 +1160,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                                             e
                                         ),
                                     }
+                                    finish_typing(&clipboard_manager_clone, &buffer_clone, secret);
                                 } else {
                                     println!("缓冲区为空，无法模拟输入 (Buffer is empty, cannot simulate typing)");
                                 }
@@ -310,6 +1168,274 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                             Err(e) => println!("剪贴板捕获错误 (Clipboard capture error): {}", e),
                         }
                     }
+                    Action::ReloadConfig => {
+                        if reloaded {
+                            println!("配置已重新加载 (Config reloaded)");
+                        }
+                    }
+                    Action::Load(source) => {
+                        let snippets = config::open_snippets(store.path());
+                        let mut cm = clipboard_manager_clone.lock().unwrap();
+                        match source.read(&mut cm, &snippets) {
+                            Ok(text) => {
+                                drop(cm); // 释放锁
+                                let text = Zeroizing::new(text);
+                                let mut buffer = buffer_clone.lock().unwrap();
+                                match source {
+                                    TextSource::Literal(_) => buffer.set_literal(text),
+                                    TextSource::Snippet(_) => buffer.set_template(text),
+                                    _ => buffer.set(text),
+                                }
+                                println!("已载入文本到缓冲区 (Text loaded into buffer)");
+                            }
+                            Err(e) => println!("错误 (Error): {}", e),
+                        }
+                    }
+                    Action::History => {
+                        let cm = clipboard_manager_clone.lock().unwrap();
+                        println!("剪贴板历史，最新的在前 (Clipboard history, most recent first):");
+                        cm.history().print();
+                        if cm.history().is_empty() {
+                            continue;
+                        }
+
+                        print!(
+                            "选择要载入缓冲区的条目，直接回车取消 (Select an entry to load into the buffer, Enter to cancel) (1-{}): ",
+                            cm.history().len()
+                        );
+                        io::stdout().flush().unwrap();
+
+                        let mut input = String::new();
+                        if io::stdin().read_line(&mut input).is_err() || input.trim().is_empty() {
+                            continue;
+                        }
+
+                        match input
+                            .trim()
+                            .parse::<usize>()
+                            .ok()
+                            .and_then(|n| cm.history().get(n))
+                        {
+                            Some(entry) => {
+                                buffer_clone
+                                    .lock()
+                                    .unwrap()
+                                    .set(Zeroizing::new(entry.text.clone()));
+                                println!(
+                                    "已载入历史条目到缓冲区 (History entry loaded into buffer)"
+                                );
+                            }
+                            None => println!("无效的选择 (Invalid choice)"),
+                        }
+                    }
+                    Action::ShowHistory => {
+                        println!(
+                            "\n剪贴板历史，最新的在前 (Clipboard history, most recent first):"
+                        );
+                        clipboard_manager_clone.lock().unwrap().history().print();
+                    }
+                    Action::ClipboardChanged => {
+                        let mut cm = clipboard_manager_clone.lock().unwrap();
+                        match cm.capture_filtered(&store.config().watch) {
+                            Ok(true) => {
+                                let text = cm.get_buffer().cloned().map(Zeroizing::new);
+                                drop(cm); // 释放锁
+                                if let Some(text) = text {
+                                    buffer_clone.lock().unwrap().set(text);
+                                }
+                                println!("\n已自动捕获剪贴板内容 (Clipboard content captured automatically)");
+                            }
+                            Ok(false) => {}
+                            // 其他程序可能仍占用剪贴板，等待下一次变化
+                            Err(e) => info!("自动捕获失败: {}", e),
+                        }
+                    }
+                    Action::ToggleWatch => {
+                        let enable = watcher.is_none();
+                        set_watching(&mut watcher, enable, &tx);
+                    }
+                    Action::ToggleSecret => {
+                        secret = !secret;
+                        clipboard_manager_clone.lock().unwrap().set_secret(secret);
+                        if secret {
+                            println!("已开启敏感内容模式 (Secret mode enabled)");
+                        } else {
+                            println!("已关闭敏感内容模式 (Secret mode disabled)");
+                        }
+                    }
+                    Action::Analyze => {
+                        let text = buffer_clone.lock().unwrap().get();
+                        match text {
+                            Some(text) => {
+                                let sim = input_simulator_clone.lock().unwrap();
+                                let report =
+                                    preflight::analyze(&store.config().preflight, &sim, &text);
+                                println!("{}", report.describe(secret));
+                            }
+                            None => println!("缓冲区为空 (Buffer is empty)"),
+                        }
+                    }
+                    Action::Slot(slot) => {
+                        // 槽位 N 优先输入绑定的片段，否则输入第 N 新的历史条目
+                        let snippets = config::open_snippets(store.path());
+                        let text = match snippets.for_slot(slot) {
+                            Some((name, snippet)) => {
+                                println!(
+                                    "\n[热键触发] 输入片段 {}... ([Hotkey triggered] Typing snippet {}...)",
+                                    name, name
+                                );
+                                Some((snippet.text.clone(), true))
+                            }
+                            None => {
+                                let entry = clipboard_manager_clone
+                                    .lock()
+                                    .unwrap()
+                                    .history()
+                                    .get(slot)
+                                    .map(|entry| (entry.text.clone(), false));
+                                if entry.is_some() {
+                                    println!(
+                                        "\n[热键触发] 输入第 {} 条历史记录... ([Hotkey triggered] Typing history entry {}...)",
+                                        slot, slot
+                                    );
+                                }
+                                entry
+                            }
+                        };
+                        match text {
+                            Some((text, snippet)) => type_loaded(
+                                store.config(),
+                                &input_simulator_clone,
+                                &clipboard_manager_clone,
+                                &buffer_clone,
+                                &text,
+                                snippet,
+                                &focus,
+                                secret,
+                                console,
+                            ),
+                            None => {
+                                println!("历史记录中没有第 {} 条 (No history entry {})", slot, slot)
+                            }
+                        }
+                    }
+                    Action::Vault => {
+                        // 每次使用时解锁，用完即丢弃，密钥和内容随之清零
+                        let vault = match unlock_vault(&store) {
+                            Ok(vault) => vault,
+                            Err(e) => {
+                                println!("错误 (Error): {}", e);
+                                continue;
+                            }
+                        };
+                        println!("密码库条目 (Vault entries):");
+                        vault.print();
+                        if vault.is_empty() {
+                            continue;
+                        }
+
+                        let name = match vault::read_line(
+                            "选择要自动输入的条目，直接回车取消 (Entry to auto-type, Enter to cancel)",
+                            false,
+                        ) {
+                            Ok(name) if !name.trim().is_empty() => name,
+                            _ => continue,
+                        };
+                        let strokes = vault.strokes(name.trim(), None);
+                        drop(vault);
+
+                        match strokes {
+                            Ok(strokes) => {
+                                let result = focus.run().and_then(|window| {
+                                    let sim = input_simulator_clone.lock().unwrap();
+                                    session::type_strokes(store.config(), &sim, window, &strokes)
+                                });
+                                match result {
+                                    Ok(_) => println!("自动输入完成 (Auto-type completed)"),
+                                    Err(e) => {
+                                        println!("模拟输入错误 (Typing simulation error): {}", e)
+                                    }
+                                }
+                            }
+                            Err(e) => println!("错误 (Error): {}", e),
+                        }
+                    }
+                    Action::Snippets => {
+                        let mut snippets = config::open_snippets(store.path());
+                        println!("片段库 (Snippet library):");
+                        snippets.print(None);
+
+                        loop {
+                            print!("输入名称开始输入，/关键词 搜索，+名称 [标签...] 保存当前缓冲区，-名称 删除，直接回车返回 (Name to type, /text to search, +name [tags...] to save the buffer, -name to delete, Enter to go back): ");
+                            io::stdout().flush().unwrap();
+
+                            let mut input = String::new();
+                            if io::stdin().read_line(&mut input).is_err() {
+                                break;
+                            }
+                            let input = input.trim();
+                            if input.is_empty() {
+                                break;
+                            }
+
+                            if let Some(query) = input.strip_prefix('/') {
+                                snippets.print(Some(query.trim()));
+                            } else if let Some(rest) = input.strip_prefix('+') {
+                                let mut parts = rest.split_whitespace();
+                                let name = parts.next().unwrap_or_default().to_string();
+                                let tags = parts.map(String::from).collect();
+                                let text = buffer_clone.lock().unwrap().get();
+                                let text = match text {
+                                    Some(text) => text,
+                                    None => {
+                                        println!("缓冲区为空 (Buffer is empty)");
+                                        continue;
+                                    }
+                                };
+                                let snippet = Snippet {
+                                    text: text.to_string(),
+                                    tags,
+                                    slot: None,
+                                };
+                                match snippets.add(&name, snippet) {
+                                    Ok(true) => println!("已替换片段 (Snippet replaced): {}", name),
+                                    Ok(false) => println!("已保存片段 (Snippet saved): {}", name),
+                                    Err(e) => println!("错误 (Error): {}", e),
+                                }
+                            } else if let Some(name) = input.strip_prefix('-') {
+                                match snippets.remove(name.trim()) {
+                                    Ok(_) => {
+                                        println!("已删除片段 (Snippet deleted): {}", name.trim())
+                                    }
+                                    Err(e) => println!("错误 (Error): {}", e),
+                                }
+                            } else {
+                                match snippets.get(input) {
+                                    Some(snippet) => {
+                                        let text = snippet.text.clone();
+                                        type_loaded(
+                                            store.config(),
+                                            &input_simulator_clone,
+                                            &clipboard_manager_clone,
+                                            &buffer_clone,
+                                            &text,
+                                            true,
+                                            &focus,
+                                            secret,
+                                            console,
+                                        );
+                                        break;
+                                    }
+                                    None => println!("片段不存在 (No such snippet): {}", input),
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    clear_buffers(&clipboard_manager_clone, &buffer_clone);
                 }
             }
             Err(e) => {
@@ -321,3 +1447,204 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+// 将文本载入缓冲区，切换到目标窗口后输入；模板变量在输入前按配置展开
+#[allow(clippy::too_many_arguments)]
+fn type_loaded(
+    config: &Config,
+    input_simulator: &Mutex<InputSimulator>,
+    clipboard_manager: &Mutex<ClipboardManager>,
+    buffer: &Mutex<Buffer>,
+    text: &str,
+    snippet: bool,
+    focus: &Focus,
+    secret: bool,
+    console: bool,
+) {
+    let text = Zeroizing::new(text.to_string());
+    if snippet {
+        buffer.lock().unwrap().set_template(text);
+    } else {
+        buffer.lock().unwrap().set(text);
+    }
+    type_buffer(
+        config,
+        input_simulator,
+        clipboard_manager,
+        buffer,
+        focus,
+        secret,
+        console,
+    );
+}
+
+// 切换到目标窗口后输入缓冲区中的内容，按配置先展开模板变量。
+// console 为 false 时不能在控制台询问缺少的值
+#[allow(clippy::too_many_arguments)]
+fn type_buffer(
+    config: &Config,
+    input_simulator: &Mutex<InputSimulator>,
+    clipboard_manager: &Mutex<ClipboardManager>,
+    buffer: &Mutex<Buffer>,
+    focus: &Focus,
+    secret: bool,
+    console: bool,
+) {
+    let (text, template) = {
+        let buffer = buffer.lock().unwrap();
+        (buffer.get(), buffer.expands_templates(config))
+    };
+    let text = match text {
+        Some(text) => text,
+        None => {
+            println!(
+                "缓冲区为空，请先捕获剪贴板 (Buffer is empty, please capture clipboard first)"
+            );
+            return;
+        }
+    };
+    let text = if template {
+        match expand_templates(clipboard_manager, &text, console) {
+            Some(text) => text,
+            None => return,
+        }
+    } else {
+        text
+    };
+
+    let result = focus.run().and_then(|window| {
+        let sim = input_simulator.lock().unwrap();
+        let mut cm = clipboard_manager.lock().unwrap();
+        session::type_text(config, &sim, &mut cm, window, &text)
+    });
+    match result {
+        Ok(_) => println!("模拟输入完成 (Typing simulation completed)"),
+        Err(e) => println!("模拟输入错误 (Typing simulation error): {}", e),
+    }
+    finish_typing(clipboard_manager, buffer, secret);
+}
+
+// 片段总是展开模板变量，命令行和菜单中输入的文本从不展开，其他来源需开启 templates_in_buffer
+fn expands_templates(config: &Config, snippet: bool, literal: bool) -> bool {
+    config.templates && (snippet || (config.templates_in_buffer && !literal))
+}
+
+// 输入前展开模板变量，失败时打印错误并返回 None
+fn expand_templates(
+    clipboard_manager: &Mutex<ClipboardManager>,
+    text: &str,
+    console: bool,
+) -> Option<Zeroizing<String>> {
+    let cm = clipboard_manager.lock().unwrap();
+    match template::expand(text, &cm, console) {
+        Ok(text) => Some(text),
+        Err(e) => {
+            println!("错误 (Error): {}", e);
+            None
+        }
+    }
+}
+
+// 一次输入结束后按配置恢复剪贴板，敏感内容模式下清除缓冲区
+fn finish_typing(
+    clipboard_manager: &Mutex<ClipboardManager>,
+    buffer: &Mutex<Buffer>,
+    secret: bool,
+) {
+    if let Err(e) = clipboard_manager.lock().unwrap().restore_after_typing() {
+        println!("错误 (Error): {}", e);
+    }
+    if secret {
+        clear_buffers(clipboard_manager, buffer);
+    }
+}
+
+// 清除两处缓冲区中的内容
+fn clear_buffers(clipboard_manager: &Mutex<ClipboardManager>, buffer: &Mutex<Buffer>) {
+    clipboard_manager.lock().unwrap().clear_buffer();
+    buffer.lock().unwrap().clear();
+    println!("\n已清除缓冲区 (Buffer cleared)");
+}
+
+// 开启或关闭剪贴板变化监听
+fn set_watching(watcher: &mut Option<ClipboardWatcher>, enable: bool, tx: &mpsc::Sender<Request>) {
+    if !enable {
+        if watcher.take().is_some() {
+            println!("已关闭自动捕获 (Auto capture disabled)");
+        }
+        return;
+    }
+    if watcher.is_some() {
+        return;
+    }
+
+    let tx_watch = tx.clone();
+    match ClipboardWatcher::start(move || {
+        let _ = tx_watch.send(Action::ClipboardChanged.into());
+    }) {
+        Ok(w) => {
+            *watcher = Some(w);
+            println!("已开启自动捕获 (Auto capture enabled)");
+        }
+        Err(e) => println!("错误 (Error): {}", e),
+    }
+}
+
+// 菜单中设置的输入参数，重新加载配置后仍然保留，选择速度模式时清除
+#[derive(Default)]
+struct Overrides {
+    delay_ms: Option<u64>,             // 按键延迟
+    custom: Option<(u64, usize, u64)>, // 自定义参数：字符延迟、批处理大小、批次延迟
+}
+
+impl Overrides {
+    fn apply(&self, simulator: &mut InputSimulator) {
+        if let Some((delay_ms, batch_size, batch_delay_ms)) = self.custom {
+            simulator.set_custom_params(delay_ms, batch_size, batch_delay_ms);
+        }
+        if let Some(delay_ms) = self.delay_ms {
+            simulator.set_delay(delay_ms);
+        }
+    }
+}
+
+// 配置重新加载后重新应用当前方案和菜单中设置的参数，热键设置变化时重新注册热键
+#[allow(clippy::too_many_arguments)]
+fn apply_reloaded_config(
+    store: &ConfigStore,
+    active_profile: &mut String,
+    overrides: &Overrides,
+    input_simulator: &Arc<Mutex<InputSimulator>>,
+    hotkey_manager: &mut HotkeyManager,
+    trigger_override: Option<&str>,
+    tx: &mpsc::Sender<Request>,
+    abort_flag: &Arc<AtomicBool>,
+    hotkeys: &mut config::HotkeyConfig,
+) {
+    let config = store.config();
+
+    // 当前方案被删除时回到默认方案
+    if config.profile(active_profile).is_none() {
+        *active_profile = config.default_profile.clone();
+    }
+    if let Some(profile) = config.profile(active_profile) {
+        let mut sim = input_simulator.lock().unwrap();
+        sim.apply_profile(active_profile, &profile);
+        overrides.apply(&mut sim);
+    }
+
+    if *hotkeys != config.hotkeys {
+        let _ = hotkey_manager.unregister();
+        match register_hotkeys(
+            hotkey_manager,
+            config,
+            trigger_override,
+            tx,
+            abort_flag.clone(),
+        ) {
+            Ok(_) => println!("热键已更新 (Hotkeys updated)"),
+            Err(e) => println!("错误 (Error): {}", e),
+        }
+        *hotkeys = config.hotkeys.clone();
+    }
+}
@@ -0,0 +1,204 @@
+// src/preflight.rs
+use crate::config::PreflightConfig;
+use crate::input_sim::{map_char_to_key, InputSimulator};
+use crate::transform;
+use serde::Deserialize;
+use std::fmt;
+use std::time::Duration;
+
+// 最多列出的行号数量
+const MAX_LISTED_LINES: usize = 10;
+
+// 可在配置中设置为阻止输入的问题
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedFlag {
+    NonBmp,          // 非BMP字符（表情符号、生僻字等），部分程序只能收到半个代理对
+    Control,         // 换行和制表符以外的控制字符
+    Unmappable,      // 当前键盘布局无法输入的字符
+    Unrepresentable, // 转写时无法用ASCII表示的字符
+    LongLines,       // 超过 max_line_width 的行
+    Duration,        // 预计耗时超过 max_duration_secs
+}
+
+impl fmt::Display for RedFlag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RedFlag::NonBmp => "非BMP字符 (non-BMP characters)",
+            RedFlag::Control => "控制字符 (control characters)",
+            RedFlag::Unmappable => {
+                "键盘布局无法输入的字符 (characters unmappable in the keyboard layout)"
+            }
+            RedFlag::Unrepresentable => {
+                "无法转写为ASCII的字符 (characters not representable in ASCII)"
+            }
+            RedFlag::LongLines => "过长的行 (lines over the width limit)",
+            RedFlag::Duration => "预计耗时过长 (estimated duration over the limit)",
+        };
+        f.write_str(name)
+    }
+}
+
+// 输入前对缓冲区的分析结果，针对执行文本转换后实际要输入的内容
+pub struct Report {
+    pub chars: usize,
+    pub lines: usize,
+    pub estimated: Duration,
+    pub non_bmp: Vec<char>,
+    pub control: Vec<char>,
+    pub unmappable: Vec<char>,
+    pub unrepresentable: Vec<char>,
+    pub max_line_width: usize,
+    pub long_lines: Vec<usize>,  // 超过宽度的行号，从1开始
+    pub red_flags: Vec<RedFlag>, // 出现且配置为阻止输入的问题
+}
+
+impl Report {
+    pub fn blocked(&self) -> bool {
+        !self.red_flags.is_empty()
+    }
+
+    // 生成可打印的摘要，敏感内容模式下只显示数量
+    pub fn describe(&self, secret: bool) -> String {
+        let mut out = String::from("预检分析 (Pre-flight analysis):\n");
+        out.push_str(&format!("  字符数 (Characters): {}\n", self.chars));
+        out.push_str(&format!("  行数 (Lines): {}\n", self.lines));
+        out.push_str(&format!(
+            "  预计耗时 (Estimated duration): {:.1} 秒 (s)\n",
+            self.estimated.as_secs_f64()
+        ));
+
+        let sections = [
+            ("非BMP字符 (Non-BMP characters)", &self.non_bmp),
+            ("控制字符 (Control characters)", &self.control),
+            (
+                "当前键盘布局无法输入 (Unmappable in the keyboard layout)",
+                &self.unmappable,
+            ),
+            (
+                "无法转写为ASCII (Not representable in ASCII)",
+                &self.unrepresentable,
+            ),
+        ];
+        for (title, chars) in sections {
+            if !chars.is_empty() {
+                out.push_str(&format!("  {}: {}\n", title, list_chars(chars, secret)));
+            }
+        }
+
+        if !self.long_lines.is_empty() {
+            let mut lines: Vec<String> = self
+                .long_lines
+                .iter()
+                .take(MAX_LISTED_LINES)
+                .map(|n| n.to_string())
+                .collect();
+            if self.long_lines.len() > MAX_LISTED_LINES {
+                lines.push("...".into());
+            }
+            out.push_str(&format!(
+                "  超过 {} 列的行 (Lines over {} columns): {} 行 (lines): {}\n",
+                self.max_line_width,
+                self.max_line_width,
+                self.long_lines.len(),
+                lines.join(", ")
+            ));
+        }
+
+        if self.blocked() {
+            let flags: Vec<String> = self.red_flags.iter().map(|f| f.to_string()).collect();
+            out.push_str(&format!("  阻止输入 (Blocked): {}", flags.join(", ")));
+        } else {
+            out.push_str("  未发现阻止输入的问题 (No blocking issues)");
+        }
+        out
+    }
+}
+
+// 按当前输入设置分析文本
+pub fn analyze(config: &PreflightConfig, simulator: &InputSimulator, text: &str) -> Report {
+    let unrepresentable = transform::unrepresentable(simulator.transforms(), text);
+    let text = transform::apply_all(simulator.transforms(), text);
+
+    let mut non_bmp = Vec::new();
+    let mut control = Vec::new();
+    let mut unmappable = Vec::new();
+    for c in text.chars() {
+        if (c as u32) > 0xFFFF {
+            push_unique(&mut non_bmp, c);
+        }
+        if matches!(c, '\r' | '\n' | '\t') {
+            continue;
+        }
+        if c.is_control() {
+            push_unique(&mut control, c);
+        } else if map_char_to_key(c).is_none() {
+            push_unique(&mut unmappable, c);
+        }
+    }
+
+    let long_lines = if config.max_line_width > 0 {
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| line.chars().count() > config.max_line_width)
+            .map(|(i, _)| i + 1)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let estimated = simulator.estimate(&text);
+    let mut report = Report {
+        chars: text.chars().count(),
+        lines: text.lines().count(),
+        estimated,
+        non_bmp,
+        control,
+        unmappable,
+        unrepresentable,
+        max_line_width: config.max_line_width,
+        long_lines,
+        red_flags: Vec::new(),
+    };
+
+    let too_slow =
+        config.max_duration_secs > 0 && estimated > Duration::from_secs(config.max_duration_secs);
+    report.red_flags = config
+        .block_on
+        .iter()
+        .copied()
+        .filter(|flag| match flag {
+            RedFlag::NonBmp => !report.non_bmp.is_empty(),
+            RedFlag::Control => !report.control.is_empty(),
+            RedFlag::Unmappable => !report.unmappable.is_empty(),
+            RedFlag::Unrepresentable => !report.unrepresentable.is_empty(),
+            RedFlag::LongLines => !report.long_lines.is_empty(),
+            RedFlag::Duration => too_slow,
+        })
+        .collect();
+    report
+}
+
+fn push_unique(chars: &mut Vec<char>, c: char) {
+    if !chars.contains(&c) {
+        chars.push(c);
+    }
+}
+
+// 控制字符和空白显示为码位
+fn list_chars(chars: &[char], secret: bool) -> String {
+    if secret {
+        return format!("{} 种 (kinds)", chars.len());
+    }
+    chars
+        .iter()
+        .map(|&c| {
+            if c.is_control() || c.is_whitespace() {
+                format!("U+{:04X}", c as u32)
+            } else {
+                c.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,615 @@
+// src/richtext.rs
+// 将剪贴板中的 HTML / RTF 内容转换为纯文本，保留段落、换行和列表符号
+use crate::source::decode_codepage;
+
+// 默认的 RTF ANSI 代码页（西欧）
+const DEFAULT_RTF_CODEPAGE: u32 = 1252;
+
+// 不包含正文的 RTF 目标组
+const RTF_SKIP_DESTINATIONS: &[&str] = &[
+    "fonttbl",
+    "colortbl",
+    "stylesheet",
+    "info",
+    "pict",
+    "object",
+    "header",
+    "headerl",
+    "headerr",
+    "footer",
+    "footerl",
+    "footerr",
+    "listtable",
+    "listoverridetable",
+    "rsidtbl",
+    "revtbl",
+    "filetbl",
+    "generator",
+    "themedata",
+    "colorschememapping",
+    "datastore",
+    "latentstyles",
+    "xmlnstbl",
+    "fldinst",
+];
+
+// HTML 转纯文本
+pub fn html_to_text(html: &str) -> String {
+    let mut text = TextBuilder::default();
+    let mut lists: Vec<Option<usize>> = Vec::new(); // None 为无序列表，Some(n) 为有序列表的下一个编号
+    let mut pre_depth = 0usize;
+    let mut skip_until: Option<String> = None; // 跳过 script/style 等元素的内容
+    let mut cell_in_row = false;
+
+    let mut rest = html_fragment(html);
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map_or("", |i| &after[i + 3..]);
+            continue;
+        }
+
+        if rest.starts_with('<') {
+            let end = match rest.find('>') {
+                Some(end) => end,
+                None => break, // 不完整的标签
+            };
+            let tag = Tag::parse(&rest[1..end]);
+            rest = &rest[end + 1..];
+
+            if let Some(skipped) = &skip_until {
+                if tag.closing && tag.name == *skipped {
+                    skip_until = None;
+                }
+                continue;
+            }
+
+            let in_list = !lists.is_empty();
+            match (tag.name.as_str(), tag.closing) {
+                ("script" | "style" | "head" | "title", false) if !tag.self_closing => {
+                    skip_until = Some(tag.name.clone());
+                }
+                ("br", _) => text.line_break(),
+                ("p", _) => text.break_lines(if in_list { 1 } else { 2 }),
+                ("h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "blockquote" | "table" | "hr", _) => {
+                    text.break_lines(2)
+                }
+                ("pre", closing) => {
+                    text.break_lines(2);
+                    if closing {
+                        pre_depth = pre_depth.saturating_sub(1);
+                    } else {
+                        pre_depth += 1;
+                    }
+                }
+                ("div" | "section" | "article" | "header" | "footer" | "dt" | "dd", _) => {
+                    text.break_lines(1)
+                }
+                ("ul", false) => {
+                    text.break_lines(if in_list { 1 } else { 2 });
+                    lists.push(None);
+                }
+                ("ol", false) => {
+                    text.break_lines(if in_list { 1 } else { 2 });
+                    lists.push(Some(1));
+                }
+                ("ul" | "ol", true) => {
+                    lists.pop();
+                    text.break_lines(if lists.is_empty() { 2 } else { 1 });
+                }
+                ("li", false) => {
+                    text.break_lines(1);
+                    let depth = lists.len().max(1);
+                    let marker = match lists.last_mut() {
+                        Some(Some(n)) => {
+                            *n += 1;
+                            format!("{}. ", *n - 1)
+                        }
+                        _ => "• ".to_string(),
+                    };
+                    text.push_raw(&"  ".repeat(depth - 1));
+                    text.push_raw(&marker);
+                }
+                ("li", true) => text.break_lines(1),
+                ("tr", _) => {
+                    text.break_lines(1);
+                    cell_in_row = false;
+                }
+                ("td" | "th", false) => {
+                    if cell_in_row {
+                        text.push_raw("\t");
+                    }
+                    cell_in_row = true;
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        // 文本内容，直到下一个标签
+        let end = rest.find('<').unwrap_or(rest.len());
+        let run = &rest[..end];
+        rest = &rest[end..];
+        if skip_until.is_some() {
+            continue;
+        }
+
+        let decoded = decode_entities(run);
+        if pre_depth > 0 {
+            text.push_raw(&decoded.replace('\r', "").replace('\u{a0}', " "));
+        } else {
+            text.push_collapsed(&decoded);
+        }
+    }
+
+    tidy(&text.out)
+}
+
+// 从 CF_HTML 数据中取出复制的片段，头部的偏移量以字节计
+fn html_fragment(data: &str) -> &str {
+    let offset = |key: &str| -> Option<usize> {
+        let start = data.find(key)? + key.len();
+        let digits: String = data[start..]
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        digits.parse().ok()
+    };
+
+    if let (Some(start), Some(end)) = (offset("StartFragment:"), offset("EndFragment:")) {
+        if let Some(fragment) = data.get(start..end) {
+            return fragment;
+        }
+    }
+    if let (Some(start), Some(end)) = (
+        data.find("<!--StartFragment-->"),
+        data.find("<!--EndFragment-->"),
+    ) {
+        if start < end {
+            return &data[start + "<!--StartFragment-->".len()..end];
+        }
+    }
+
+    // 跳过 "Version:0.9 ..." 头部
+    match data.find('<') {
+        Some(start) if data.starts_with("Version:") => &data[start..],
+        _ => data,
+    }
+}
+
+struct Tag {
+    name: String,
+    closing: bool,
+    self_closing: bool,
+}
+
+impl Tag {
+    fn parse(raw: &str) -> Tag {
+        let closing = raw.starts_with('/');
+        let name = raw
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == ':')
+            .collect::<String>()
+            .to_ascii_lowercase();
+        Tag {
+            name,
+            closing,
+            self_closing: raw.ends_with('/'),
+        }
+    }
+}
+
+// 解码 HTML 实体，无法识别的实体原样保留
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&semi| semi <= 10)
+            .and_then(|semi| entity(&rest[1..semi + 1]).map(|c| (c, semi + 2)));
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn entity(name: &str) -> Option<char> {
+    if let Some(num) = name.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+
+    let c = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "ensp" | "emsp" | "thinsp" => ' ',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "bull" => '•',
+        "middot" => '·',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "deg" => '°',
+        "times" => '×',
+        _ => return None,
+    };
+    Some(c)
+}
+
+// 拼接输出文本，合并多余的空白和空行
+#[derive(Default)]
+struct TextBuilder {
+    out: String,
+    pending_space: bool,
+}
+
+impl TextBuilder {
+    // 按 HTML 规则合并空白
+    fn push_collapsed(&mut self, text: &str) {
+        for c in text.chars() {
+            if c.is_whitespace() && c != '\u{a0}' {
+                self.pending_space = true;
+                continue;
+            }
+            if self.pending_space && !self.out.is_empty() && !self.out.ends_with(['\n', ' ', '\t'])
+            {
+                self.out.push(' ');
+            }
+            self.pending_space = false;
+            self.out.push(if c == '\u{a0}' { ' ' } else { c });
+        }
+    }
+
+    fn push_raw(&mut self, text: &str) {
+        self.pending_space = false;
+        self.out.push_str(text);
+    }
+
+    fn line_break(&mut self) {
+        self.pending_space = false;
+        self.out.push('\n');
+    }
+
+    // 保证以至少 n 个换行结束；开头不插入空行
+    fn break_lines(&mut self, n: usize) {
+        self.pending_space = false;
+        if self.out.trim().is_empty() {
+            return;
+        }
+        let existing = self.out.chars().rev().take_while(|&c| c == '\n').count();
+        for _ in existing..n {
+            self.out.push('\n');
+        }
+    }
+}
+
+// RTF 转纯文本
+pub fn rtf_to_text(rtf: &[u8]) -> String {
+    struct Group {
+        skip: bool,
+        uc: usize, // \uN 之后需要跳过的替代字符数
+    }
+
+    let mut out = String::new();
+    let mut pending: Vec<u8> = Vec::new(); // \'hh 字节，按代码页解码
+    let mut codepage = DEFAULT_RTF_CODEPAGE;
+    let mut stack: Vec<Group> = Vec::new();
+    let mut group = Group { skip: false, uc: 1 };
+    let mut skip_fallback = 0usize;
+    let mut high_surrogate: Option<(u32, usize)> = None; // 高位代理项和当时的输出长度，与紧接着的 \uN 组成一个字符
+
+    let flush = |pending: &mut Vec<u8>, out: &mut String, codepage: u32| {
+        if !pending.is_empty() {
+            let text = decode_codepage(pending, codepage)
+                .unwrap_or_else(|| String::from_utf8_lossy(pending).into_owned());
+            out.push_str(&text);
+            pending.clear();
+        }
+    };
+
+    let mut i = 0;
+    while i < rtf.len() {
+        let b = rtf[i];
+        i += 1;
+        match b {
+            b'{' => {
+                stack.push(Group {
+                    skip: group.skip,
+                    uc: group.uc,
+                });
+                skip_fallback = 0;
+            }
+            b'}' => {
+                flush(&mut pending, &mut out, codepage);
+                if let Some(outer) = stack.pop() {
+                    group = outer;
+                }
+                skip_fallback = 0;
+            }
+            b'\r' | b'\n' => {}
+            b'\\' => {
+                let Some(&next) = rtf.get(i) else { break };
+                if !next.is_ascii_alphabetic() {
+                    // 控制符号
+                    i += 1;
+                    match next {
+                        b'\'' => {
+                            let byte = rtf
+                                .get(i..i + 2)
+                                .and_then(|hex| std::str::from_utf8(hex).ok())
+                                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                            i += 2;
+                            if skip_fallback > 0 {
+                                skip_fallback -= 1;
+                            } else if let (Some(byte), false) = (byte, group.skip) {
+                                pending.push(byte);
+                            }
+                        }
+                        b'*' => group.skip = true,
+                        _ if group.skip => {}
+                        b'\\' | b'{' | b'}' => {
+                            flush(&mut pending, &mut out, codepage);
+                            out.push(next as char);
+                        }
+                        b'~' => {
+                            flush(&mut pending, &mut out, codepage);
+                            out.push(' ');
+                        }
+                        b'_' => {
+                            flush(&mut pending, &mut out, codepage);
+                            out.push('-');
+                        }
+                        b'\r' | b'\n' => {
+                            flush(&mut pending, &mut out, codepage);
+                            out.push('\n');
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                // 控制字：字母 + 可选的数字参数 + 可选的空格分隔符
+                let start = i;
+                while i < rtf.len() && rtf[i].is_ascii_alphabetic() {
+                    i += 1;
+                }
+                let word = std::str::from_utf8(&rtf[start..i]).unwrap_or("");
+                let param_start = i;
+                if i < rtf.len() && rtf[i] == b'-' {
+                    i += 1;
+                }
+                while i < rtf.len() && rtf[i].is_ascii_digit() {
+                    i += 1;
+                }
+                let param: Option<i32> = std::str::from_utf8(&rtf[param_start..i])
+                    .ok()
+                    .and_then(|p| p.parse().ok());
+                if i < rtf.len() && rtf[i] == b' ' {
+                    i += 1;
+                }
+
+                if RTF_SKIP_DESTINATIONS.contains(&word) {
+                    group.skip = true;
+                    continue;
+                }
+                match word {
+                    "ansicpg" => {
+                        if let Some(cp) = param {
+                            codepage = cp as u32;
+                        }
+                    }
+                    "uc" => group.uc = param.unwrap_or(1).max(0) as usize,
+                    _ if group.skip => {}
+                    "u" => {
+                        flush(&mut pending, &mut out, codepage);
+                        if let Some(code) = param {
+                            // 参数为有符号16位整数，BMP 之外的字符写成两个代理项
+                            let code = if code < 0 { code + 65536 } else { code } as u32;
+                            let decoded = match (high_surrogate.take(), code) {
+                                (Some((high, len)), 0xDC00..=0xDFFF) if len == out.len() => {
+                                    char::from_u32(
+                                        0x10000 + ((high - 0xD800) << 10) + (code - 0xDC00),
+                                    )
+                                }
+                                (_, 0xD800..=0xDBFF) => {
+                                    high_surrogate = Some((code, out.len()));
+                                    None
+                                }
+                                _ => char::from_u32(code),
+                            };
+                            if let Some(c) = decoded {
+                                out.push(c);
+                            }
+                        }
+                        skip_fallback = group.uc;
+                    }
+                    _ => {
+                        let text = match word {
+                            "par" | "line" | "sect" | "page" | "row" => "\n",
+                            "tab" | "cell" => "\t",
+                            "emdash" => "—",
+                            "endash" => "–",
+                            "bullet" => "•",
+                            "lquote" => "‘",
+                            "rquote" => "’",
+                            "ldblquote" => "“",
+                            "rdblquote" => "”",
+                            _ => continue,
+                        };
+                        flush(&mut pending, &mut out, codepage);
+                        out.push_str(text);
+                    }
+                }
+            }
+            _ => {
+                if skip_fallback > 0 {
+                    skip_fallback -= 1;
+                } else if !group.skip {
+                    if b.is_ascii() {
+                        flush(&mut pending, &mut out, codepage);
+                        out.push(b as char);
+                    } else {
+                        pending.push(b);
+                    }
+                }
+            }
+        }
+    }
+    flush(&mut pending, &mut out, codepage);
+
+    tidy(&out)
+}
+
+// 去掉行尾空白，最多保留一个空行
+fn tidy(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut blank_lines = 0;
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_lines += 1;
+            if blank_lines > 1 || out.is_empty() {
+                continue;
+            }
+        } else {
+            blank_lines = 0;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 按 CF_HTML 格式生成数据，偏移量以字节计
+    fn cf_html(before: &str, fragment: &str, after: &str) -> String {
+        let header_len = "Version:0.9\r\nStartHTML:0000000000\r\nEndHTML:0000000000\r\n\
+                          StartFragment:0000000000\r\nEndFragment:0000000000\r\n"
+            .len();
+        let start = header_len + before.len();
+        let end = start + fragment.len();
+        format!(
+            "Version:0.9\r\nStartHTML:{:010}\r\nEndHTML:{:010}\r\n\
+             StartFragment:{:010}\r\nEndFragment:{:010}\r\n{}{}{}",
+            header_len,
+            end + after.len(),
+            start,
+            end,
+            before,
+            fragment,
+            after
+        )
+    }
+
+    #[test]
+    fn html_fragment_uses_byte_offsets() {
+        let data = cf_html(
+            "<html><head><title>Café – überschrift</title></head><body><p>外面</p>",
+            "<p>Hello &amp; <b>world</b></p>",
+            "<p>after</p></body></html>",
+        );
+        assert_eq!(html_to_text(&data), "Hello & world");
+    }
+
+    #[test]
+    fn html_fragment_falls_back_to_markers() {
+        let data = "Version:0.9\r\n<html><body>skip<!--StartFragment-->\
+                    <ul><li>one</li><li>two</li></ul><!--EndFragment-->skip</body></html>";
+        assert_eq!(html_to_text(data), "• one\n• two");
+    }
+
+    #[test]
+    fn html_fragment_ignores_invalid_offsets() {
+        let data =
+            "Version:0.9\r\nStartFragment:0000009999\r\nEndFragment:0000000010\r\n<p>text</p>";
+        assert_eq!(html_to_text(data), "text");
+    }
+
+    #[test]
+    fn html_blocks_lists_and_preformatted_text() {
+        let html = "<h1>Title</h1><p>a  \n b&nbsp;c</p><ol><li>x</li><li>y</li></ol>\
+                    <pre>  keep\n    indent</pre><script>var s = '<p>';</script>";
+        assert_eq!(
+            html_to_text(html),
+            "Title\n\na b c\n\n1. x\n2. y\n\n  keep\n    indent"
+        );
+    }
+
+    #[test]
+    fn html_unknown_entities_are_kept() {
+        assert_eq!(
+            decode_entities("a &foo; &#x4E2D;&#25991; &lt;b&gt;"),
+            "a &foo; 中文 <b>"
+        );
+    }
+
+    #[test]
+    fn rtf_unicode_skips_one_fallback_by_default() {
+        let rtf = br"{\rtf1\ansi{\fonttbl{\f0 Arial;}}caf\u233 e\par na\u239?ve}";
+        assert_eq!(rtf_to_text(rtf), "café\nnaïve");
+    }
+
+    #[test]
+    fn rtf_unicode_skips_uc_fallbacks() {
+        // \uc2 时跳过两个替代字符，\'hh 也算一个
+        let rtf = br"{\rtf1\uc2 a\u8212\'2d\'2db c\u8212--d}";
+        assert_eq!(rtf_to_text(rtf), "a—b c—d");
+    }
+
+    #[test]
+    fn rtf_surrogate_pairs_are_combined() {
+        let rtf = br"{\rtf1 ok \u-10179?\u-8704?!}";
+        assert_eq!(rtf_to_text(rtf), "ok 😀!");
+        // 不成对的代理项被丢弃
+        let rtf = br"{\rtf1 a\u-10179?b\u-8704?c}";
+        assert_eq!(rtf_to_text(rtf), "abc");
+    }
+
+    #[test]
+    fn rtf_uc_is_scoped_to_group() {
+        let rtf = br"{\rtf1{\uc0 \u20013}\u25991?}";
+        assert_eq!(rtf_to_text(rtf), "中文");
+    }
+
+    #[test]
+    fn rtf_negative_unicode_parameter() {
+        // 大于 32767 的码位写成负数
+        let rtf = br"{\rtf1 \u-21504?}";
+        assert_eq!(rtf_to_text(rtf), "가");
+    }
+
+    #[test]
+    fn rtf_skips_ignorable_destinations() {
+        let rtf = br"{\rtf1{\*\generator Riched20;}{\*\unknown hidden}visible\tab x\{y\}}";
+        assert_eq!(rtf_to_text(rtf), "visible\tx{y}");
+    }
+}
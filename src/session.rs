@@ -0,0 +1,290 @@
+// src/session.rs
+use crate::click;
+use crate::clipboard::ClipboardManager;
+use crate::config::{Config, TargetConfig, TargetMode};
+use crate::input_sim::{IndentPolicy, InputSimulator, KeyStroke};
+use crate::preflight;
+use crate::transform;
+use crate::verify;
+use crate::window::{self, WindowInfo};
+use log::{info, warn};
+use std::thread;
+use std::time::{Duration, Instant};
+use winapi::shared::windef::HWND;
+use zeroize::Zeroizing;
+
+// 检查前台窗口的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// 一次输入会话：在输入时根据前台窗口选择配置方案，执行预检和方案中的文本转换，然后模拟输入。
+// 指定窗口时在后台向该窗口输入，并根据该窗口选择配置方案。开启校验时输入后读回输入框内容，
+// 读回的是整个输入框，因此输入框在输入前应为空
+pub fn type_text(
+    config: &Config,
+    simulator: &InputSimulator,
+    clipboard: &mut ClipboardManager,
+    window: Option<HWND>,
+    text: &str,
+) -> Result<(), String> {
+    let simulator = for_window(config, simulator, window);
+
+    let report = preflight::analyze(&config.preflight, &simulator, text);
+    if report.blocked() {
+        let flags: Vec<String> = report.red_flags.iter().map(|f| f.to_string()).collect();
+        warn!("预检未通过，已阻止输入: {}", flags.join(", "));
+        return Err(format!(
+            "预检未通过，已阻止输入 (Blocked by pre-flight checks): {}",
+            flags.join(", ")
+        ));
+    }
+
+    // 只记录数量，避免敏感内容写入日志
+    let lost = transform::unrepresentable(simulator.transforms(), text).len();
+    if lost > 0 {
+        warn!("{} 种字符无法转写为ASCII，将输入为 '?'", lost);
+    }
+
+    let text = transform::apply_all(simulator.transforms(), text);
+    simulator.simulate_typing(&text)?;
+
+    if config.verify.enabled {
+        if window.is_some() {
+            // 投递的消息无法传递修饰键，不能发送全选和复制
+            warn!("后台输入不支持校验，已跳过");
+        } else if simulator.indent() != IndentPolicy::Keep {
+            // 编辑器自动插入的缩进会出现在读回的内容中，与发送的文本无法对应
+            warn!("开启缩进补偿时不支持校验，已跳过");
+        } else {
+            verify_typed(config, &simulator, clipboard, &text)?;
+        }
+    }
+    Ok(())
+}
+
+// 选中并复制输入框内容，与发送的文本比较，不一致时返回第一个不一致的位置。
+// 设置了重试次数时删除不一致处之后的内容，用较慢的方案从该处重新输入后再次校验
+fn verify_typed(
+    config: &Config,
+    simulator: &InputSimulator,
+    clipboard: &mut ClipboardManager,
+    text: &str,
+) -> Result<(), String> {
+    if !clipboard.can_restore()? {
+        warn!("剪贴板中有无法保存的内容（例如图元文件），为避免丢失已跳过校验");
+        return Ok(());
+    }
+
+    let strokes = Zeroizing::new(simulator.plan(text));
+    let sent = verify::sent_text(&strokes);
+
+    let mut divergence = match read_divergence(config, simulator, clipboard, &sent)? {
+        None => {
+            info!("输入校验通过，共 {} 个字符", sent.chars().count());
+            return Ok(());
+        }
+        Some(divergence) => divergence,
+    };
+
+    let retries = config.verify.retries;
+    if retries > 0 {
+        // 只使用重试方案的速度设置，按键仍按原方案生成
+        let profile = config
+            .profile(&config.verify.retry_profile)
+            .ok_or_else(|| format!("配置方案不存在: {}", config.verify.retry_profile))?;
+        let mut slower = simulator.clone();
+        slower.set_custom_params(profile.delay_ms, profile.batch_size, profile.batch_delay_ms);
+
+        for attempt in 1..=retries {
+            if slower.aborted() {
+                return Err("输入已中止".into());
+            }
+            warn!(
+                "输入校验失败，从第 {} 行第 {} 列重新输入（第 {}/{} 次）",
+                divergence.line, divergence.column, attempt, retries
+            );
+            slower.continue_strokes(&verify::delete_tail(&divergence, config.verify.delete))?;
+            slower.continue_strokes(verify::resume_from(&strokes, divergence.offset))?;
+
+            divergence = match read_divergence(config, &slower, clipboard, &sent)? {
+                None => {
+                    info!("重新输入后校验通过，共 {} 个字符", sent.chars().count());
+                    return Ok(());
+                }
+                Some(divergence) => divergence,
+            };
+        }
+    }
+
+    warn!("输入校验失败，第 {} 个字符处不一致", divergence.offset + 1);
+    Err(format!(
+        "输入校验失败 (Verification failed): {}",
+        divergence.describe(clipboard.is_secret())
+    ))
+}
+
+// 读回输入框内容并与发送的文本比较，读回后光标在取消选中的按键所到的位置。
+// 输入中止后不再读回
+fn read_divergence(
+    config: &Config,
+    simulator: &InputSimulator,
+    clipboard: &mut ClipboardManager,
+    sent: &str,
+) -> Result<Option<verify::Divergence>, String> {
+    if simulator.aborted() {
+        return Err("输入已中止".into());
+    }
+    let select = verify::key_strokes(&config.verify.select)?;
+    let copy = verify::key_strokes(&config.verify.copy)?;
+    let deselect = verify::key_strokes(&config.verify.deselect)?;
+
+    let copied = clipboard.read_back(
+        || {
+            simulator.continue_strokes(&select)?;
+            simulator.continue_strokes(&copy)
+        },
+        Duration::from_millis(config.verify.wait_ms),
+    );
+    // 无论是否读取成功、是否中止都取消选中
+    simulator.finish_strokes(&deselect)?;
+    let copied = copied?;
+
+    Ok(verify::first_divergence(sent, &copied))
+}
+
+// 输入已解析好的按键序列（自动输入序列），不执行文本转换，避免改变密码等内容
+pub fn type_strokes(
+    config: &Config,
+    simulator: &InputSimulator,
+    window: Option<HWND>,
+    strokes: &[KeyStroke],
+) -> Result<(), String> {
+    let simulator = for_window(config, simulator, window);
+    simulator.send_strokes(strokes)
+}
+
+// 激活目标窗口、等待它成为前台窗口或等待用户点击，超时返回错误。
+// 目标就绪后等待 settle_ms，让窗口处理激活消息后再开始输入
+pub fn focus_target(target: &TargetConfig) -> Result<WindowInfo, String> {
+    let window = match target.mode {
+        TargetMode::Click => clicked_target(target)?.1,
+        TargetMode::Activate | TargetMode::Wait => foreground_target(target)?,
+    };
+    thread::sleep(Duration::from_millis(target.settle_ms));
+    Ok(window)
+}
+
+// 后台输入：查找目标窗口中接收输入的控件，不切换前台窗口。
+// 点击模式下使用点击位置下的控件，否则使用目标窗口中拥有焦点的控件
+pub fn find_target(target: &TargetConfig) -> Result<(HWND, WindowInfo), String> {
+    let (hwnd, window) = match target.mode {
+        TargetMode::Click => clicked_target(target)?,
+        TargetMode::Activate | TargetMode::Wait => {
+            let rule = target.rule();
+            let deadline = Instant::now() + Duration::from_secs(target.timeout_secs);
+            loop {
+                if let Some((hwnd, window)) = window::find_window(|window| rule.matches(window)) {
+                    info!("找到目标窗口: \"{}\"", window.title);
+                    break (window::focused_control(hwnd), window);
+                }
+                if Instant::now() >= deadline {
+                    return Err(format!(
+                        "{}秒内未找到目标窗口 (Target window not found within {} seconds)",
+                        target.timeout_secs, target.timeout_secs
+                    ));
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+    };
+    info!("后台输入的控件类名: {}", window::window_class(hwnd));
+    thread::sleep(Duration::from_millis(target.settle_ms));
+    Ok((hwnd, window))
+}
+
+// 返回点击位置下的控件和它所在的顶层窗口
+fn clicked_target(target: &TargetConfig) -> Result<(HWND, WindowInfo), String> {
+    let hwnd = click::wait_for_click(Duration::from_secs(target.timeout_secs))?;
+    let window = WindowInfo::from_hwnd(window::root_window(hwnd))
+        .ok_or("无法获取点击的窗口 (Cannot identify the clicked window)")?;
+    if target.is_set() && !target.rule().matches(&window) {
+        return Err(format!(
+            "点击的窗口不是目标窗口 (The clicked window is not the target): {}",
+            window.title
+        ));
+    }
+    info!("已点击目标窗口: \"{}\"", window.title);
+    Ok((hwnd, window))
+}
+
+fn foreground_target(target: &TargetConfig) -> Result<WindowInfo, String> {
+    let rule = target.rule();
+    let deadline = Instant::now() + Duration::from_secs(target.timeout_secs);
+    let mut found = false;
+
+    loop {
+        if let Some(window) = WindowInfo::foreground().filter(|window| rule.matches(window)) {
+            info!("目标窗口已在前台: \"{}\"", window.title);
+            return Ok(window);
+        }
+
+        // 目标窗口可能稍后才出现，激活失败时也会继续重试
+        if target.mode == TargetMode::Activate {
+            if let Some((hwnd, window)) = window::find_window(|window| rule.matches(window)) {
+                let activated = window::activate(hwnd);
+                if !found {
+                    info!("找到目标窗口: \"{}\"，正在激活", window.title);
+                    if !activated {
+                        warn!("激活目标窗口失败，将继续重试");
+                    }
+                    found = true;
+                }
+            }
+        }
+
+        if Instant::now() >= deadline {
+            return Err(match (target.mode, found) {
+                (TargetMode::Activate, false) => format!(
+                    "{}秒内未找到目标窗口 (Target window not found within {} seconds)",
+                    target.timeout_secs, target.timeout_secs
+                ),
+                _ => format!(
+                    "{}秒内目标窗口未成为前台窗口 (Target window was not in the foreground within {} seconds)",
+                    target.timeout_secs, target.timeout_secs
+                ),
+            });
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+// 根据前台窗口（后台输入时为目标窗口）选择配置方案
+fn for_window(config: &Config, simulator: &InputSimulator, target: Option<HWND>) -> InputSimulator {
+    let mut simulator = simulator.clone();
+
+    let window = match target {
+        Some(hwnd) => {
+            simulator.set_window(hwnd);
+            WindowInfo::from_hwnd(window::root_window(hwnd))
+        }
+        None => WindowInfo::foreground(),
+    };
+    match window {
+        Some(window) => {
+            info!(
+                "目标窗口: \"{}\" 类名: {} 进程: {}",
+                window.title,
+                window.class,
+                window.exe.as_deref().unwrap_or("?")
+            );
+            match config.profile_for_window(&window) {
+                Some((name, profile)) => {
+                    info!("根据目标窗口选择配置方案: {}", name);
+                    simulator.apply_profile(name, profile);
+                }
+                None => info!("没有匹配的窗口规则，使用当前配置方案"),
+            }
+        }
+        None => info!("无法获取目标窗口，使用当前配置方案"),
+    }
+    simulator
+}
@@ -0,0 +1,167 @@
+// src/snippets.rs
+use crate::config;
+use crate::history::preview;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+pub const SNIPPETS_FILE_NAME: &str = "snippets.toml";
+
+// 一个命名片段，例如
+// [snippet.signature]
+// text = "..."
+// tags = ["email"]
+// slot = 2
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snippet {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<usize>, // 绑定的热键槽位（前导键 + N），优先于剪贴板历史
+}
+
+impl Snippet {
+    // 名称、标签或内容包含关键词（不区分大小写）
+    fn matches(&self, name: &str, query: &str) -> bool {
+        let query = query.to_lowercase();
+        name.to_lowercase().contains(&query)
+            || self
+                .tags
+                .iter()
+                .any(|tag| tag.to_lowercase().contains(&query))
+            || self.text.to_lowercase().contains(&query)
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct SnippetFile {
+    #[serde(default)]
+    snippet: BTreeMap<String, Snippet>,
+}
+
+// 保存在文件中的片段库，每个名称对应一个片段
+pub struct SnippetStore {
+    snippets: BTreeMap<String, Snippet>,
+    path: Option<PathBuf>, // 文件无法读取时为空，避免保存时覆盖原有内容
+}
+
+impl SnippetStore {
+    pub fn new() -> Self {
+        SnippetStore {
+            snippets: BTreeMap::new(),
+            path: None,
+        }
+    }
+
+    // 从文件加载片段库，文件不存在时为空
+    pub fn open(path: PathBuf) -> Self {
+        let mut store = SnippetStore::new();
+        if path.exists() {
+            match std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| toml::from_str::<SnippetFile>(&text).map_err(|e| e.to_string()))
+            {
+                Ok(file) => {
+                    store.snippets = file.snippet;
+                    info!("已加载 {} 个片段", store.snippets.len());
+                }
+                Err(e) => {
+                    warn!("无法读取片段库 {}: {}", path.display(), e);
+                    return store;
+                }
+            }
+        }
+        store.path = Some(path);
+        store
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Snippet> {
+        self.snippets.get(name)
+    }
+
+    // 绑定到槽位 N 的片段
+    pub fn for_slot(&self, slot: usize) -> Option<(&str, &Snippet)> {
+        self.snippets
+            .iter()
+            .find(|(_, snippet)| snippet.slot == Some(slot))
+            .map(|(name, snippet)| (name.as_str(), snippet))
+    }
+
+    // 添加片段并保存，同名片段会被替换；返回是否替换了已有片段
+    pub fn add(&mut self, name: &str, snippet: Snippet) -> Result<bool, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("片段名称不能为空".into());
+        }
+        if snippet.text.is_empty() {
+            return Err("片段内容为空".into());
+        }
+        if let Some(slot) = snippet.slot {
+            // 一个槽位只绑定一个片段
+            for (other, existing) in self.snippets.iter_mut() {
+                if other != name && existing.slot == Some(slot) {
+                    info!("片段 {} 的槽位 {} 已改绑到 {}", other, slot, name);
+                    existing.slot = None;
+                }
+            }
+        }
+
+        let replaced = self.snippets.insert(name.to_string(), snippet).is_some();
+        self.save()?;
+        Ok(replaced)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<(), String> {
+        if self.snippets.remove(name).is_none() {
+            return Err(format!("片段不存在: {}", name));
+        }
+        self.save()
+    }
+
+    // 打印片段列表，设置关键词时只列出名称、标签或内容匹配的片段
+    pub fn print(&self, query: Option<&str>) {
+        let mut found = false;
+        for (name, snippet) in &self.snippets {
+            if !query.is_none_or(|query| snippet.matches(name, query)) {
+                continue;
+            }
+            found = true;
+
+            let mut label = name.clone();
+            if let Some(slot) = snippet.slot {
+                label.push_str(&format!(" [{}]", slot));
+            }
+            if !snippet.tags.is_empty() {
+                label.push_str(&format!(" #{}", snippet.tags.join(" #")));
+            }
+            println!("  {}: {}", label, preview(&snippet.text, 60));
+        }
+
+        if !found {
+            match query {
+                Some(query) => println!("没有匹配的片段 (No snippets match): {}", query),
+                None => println!("片段库为空 (Snippet library is empty)"),
+            }
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or_else(|| "片段库无法读取或位置未知，未保存".to_string())?;
+
+        let file = SnippetFile {
+            snippet: self.snippets.clone(),
+        };
+        let text = toml::to_string(&file).map_err(|e| e.to_string())?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("无法创建目录 {}: {}", dir.display(), e))?;
+        }
+        config::write_atomic(path, text.as_bytes())
+            .map_err(|e| format!("无法保存片段库 {}: {}", path.display(), e))
+    }
+}
@@ -0,0 +1,217 @@
+// src/source.rs
+use crate::clipboard::ClipboardManager;
+use crate::snippets::SnippetStore;
+use log::{info, warn};
+use std::io::Read;
+use std::path::PathBuf;
+use std::ptr::null_mut;
+use winapi::um::stringapiset::MultiByteToWideChar;
+use winapi::um::winnls::MB_ERR_INVALID_CHARS;
+
+// GBK 代码页
+const CP_GBK: u32 = 936;
+
+// 待输入文本的来源
+pub enum TextSource {
+    Clipboard,
+    Stdin,
+    File(PathBuf),
+    Literal(String),
+    History(usize),  // 第 n 新的剪贴板历史条目
+    Snippet(String), // 片段库中的命名片段
+}
+
+impl TextSource {
+    // 读取文本；剪贴板来源会同时更新 ClipboardManager 的缓冲区和历史
+    pub fn read(
+        &self,
+        clipboard: &mut ClipboardManager,
+        snippets: &SnippetStore,
+    ) -> Result<String, String> {
+        match self {
+            TextSource::Clipboard => {
+                clipboard.capture_clipboard()?;
+                clipboard
+                    .get_buffer()
+                    .cloned()
+                    .ok_or_else(|| "剪贴板为空".into())
+            }
+            TextSource::Stdin => {
+                let mut bytes = Vec::new();
+                std::io::stdin()
+                    .read_to_end(&mut bytes)
+                    .map_err(|e| format!("无法读取标准输入: {}", e))?;
+                let text = decode_bytes(&bytes);
+                info!("已从标准输入读取，长度: {}", text.len());
+                Ok(text)
+            }
+            TextSource::File(path) => {
+                let bytes = std::fs::read(path)
+                    .map_err(|e| format!("无法读取文件 {}: {}", path.display(), e))?;
+                let text = decode_bytes(&bytes);
+                info!("已从文件读取，长度: {}", text.len());
+                Ok(text)
+            }
+            TextSource::Literal(text) => Ok(text.clone()),
+            TextSource::History(n) => clipboard
+                .history()
+                .get(*n)
+                .map(|entry| entry.text.clone())
+                .ok_or_else(|| format!("剪贴板历史中没有第 {} 条", n)),
+            TextSource::Snippet(name) => snippets
+                .get(name)
+                .map(|snippet| snippet.text.clone())
+                .ok_or_else(|| format!("片段不存在: {}", name)),
+        }
+    }
+}
+
+// 检测编码并解码：BOM > 无BOM的UTF-16 > UTF-8 > GBK
+pub fn decode_bytes(bytes: &[u8]) -> String {
+    if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return String::from_utf8_lossy(rest).into_owned();
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        return decode_utf16(rest, false);
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        return decode_utf16(rest, true);
+    }
+
+    // 没有BOM时，根据零字节的位置判断是否为UTF-16
+    if bytes.len() >= 2 && bytes.len().is_multiple_of(2) {
+        let pairs = bytes.len() / 2;
+        let odd_zeros = bytes.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+        let even_zeros = bytes.iter().step_by(2).filter(|&&b| b == 0).count();
+        if odd_zeros * 4 >= pairs && even_zeros == 0 {
+            return decode_utf16(bytes, false);
+        }
+        if even_zeros * 4 >= pairs && odd_zeros == 0 {
+            return decode_utf16(bytes, true);
+        }
+    }
+
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return text.to_owned();
+    }
+
+    match decode_gbk(bytes) {
+        Some(text) => {
+            info!("文本按GBK编码解码");
+            text
+        }
+        None => {
+            warn!("无法识别文本编码，按UTF-8替换无效字符");
+            String::from_utf8_lossy(bytes).into_owned()
+        }
+    }
+}
+
+fn decode_utf16(bytes: &[u8], big_endian: bool) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| {
+            if big_endian {
+                u16::from_be_bytes([pair[0], pair[1]])
+            } else {
+                u16::from_le_bytes([pair[0], pair[1]])
+            }
+        })
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn decode_gbk(bytes: &[u8]) -> Option<String> {
+    decode_codepage(bytes, CP_GBK)
+}
+
+// 按Windows代码页解码，遇到无效字符时返回None
+pub fn decode_codepage(bytes: &[u8], codepage: u32) -> Option<String> {
+    if bytes.is_empty() {
+        return Some(String::new());
+    }
+
+    let len = i32::try_from(bytes.len()).ok()?;
+    unsafe {
+        // 先获取所需长度，遇到无效字符时失败
+        let wide_len = MultiByteToWideChar(
+            codepage,
+            MB_ERR_INVALID_CHARS,
+            bytes.as_ptr() as *const i8,
+            len,
+            null_mut(),
+            0,
+        );
+        if wide_len <= 0 {
+            return None;
+        }
+
+        let mut wide = vec![0u16; wide_len as usize];
+        let written = MultiByteToWideChar(
+            codepage,
+            MB_ERR_INVALID_CHARS,
+            bytes.as_ptr() as *const i8,
+            len,
+            wide.as_mut_ptr(),
+            wide_len,
+        );
+        if written <= 0 {
+            return None;
+        }
+        wide.truncate(written as usize);
+        Some(String::from_utf16_lossy(&wide))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(text: &str, big_endian: bool) -> Vec<u8> {
+        text.encode_utf16()
+            .flat_map(|unit| {
+                if big_endian {
+                    unit.to_be_bytes()
+                } else {
+                    unit.to_le_bytes()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn utf8_with_and_without_bom() {
+        assert_eq!(decode_bytes("héllo 中文".as_bytes()), "héllo 中文");
+        assert_eq!(decode_bytes(b"\xEF\xBB\xBFabc"), "abc");
+    }
+
+    #[test]
+    fn utf16_with_bom() {
+        let mut le = vec![0xFF, 0xFE];
+        le.extend(utf16("line1\r\nπ 😀", false));
+        assert_eq!(decode_bytes(&le), "line1\r\nπ 😀");
+
+        let mut be = vec![0xFE, 0xFF];
+        be.extend(utf16("line1\r\nπ 😀", true));
+        assert_eq!(decode_bytes(&be), "line1\r\nπ 😀");
+    }
+
+    #[test]
+    fn utf16_without_bom_is_detected_from_zero_bytes() {
+        assert_eq!(decode_bytes(&utf16("plain text", false)), "plain text");
+        assert_eq!(decode_bytes(&utf16("plain text", true)), "plain text");
+        // 四分之一的字符为 ASCII 即可识别
+        assert_eq!(decode_bytes(&utf16("中文中a", false)), "中文中a");
+        assert_eq!(decode_bytes(&utf16("中文中a", true)), "中文中a");
+    }
+
+    #[test]
+    fn utf16_heuristic_needs_zeros_on_one_side_only() {
+        // 两侧都有零字节时不按 UTF-16 解码
+        assert_eq!(decode_bytes(b"a\0\0b"), "a\0\0b");
+        // 零字节少于四分之一时按 UTF-8 解码
+        assert_eq!(decode_bytes(b"abcd\0fghij"), "abcd\0fghij");
+        // 奇数长度不可能是 UTF-16
+        assert_eq!(decode_bytes(b"a\0b"), "a\0b");
+    }
+}
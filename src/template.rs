@@ -0,0 +1,260 @@
+// src/template.rs
+use crate::clipboard::ClipboardManager;
+use log::warn;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use winapi::shared::guiddef::GUID;
+use winapi::shared::winerror::SUCCEEDED;
+use winapi::um::combaseapi::CoCreateGuid;
+use winapi::um::minwinbase::SYSTEMTIME;
+use winapi::um::sysinfoapi::GetLocalTime;
+use zeroize::Zeroizing;
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+// 展开模板变量，在输入前执行。片段总是展开，剪贴板、历史、文件和标准输入中的文本
+// 需开启 templates_in_buffer，命令行和菜单中输入的文本从不展开：
+//   {{date}} / {{date:%Y-%m-%d %H:%M}}  当前本地时间
+//   {{env:USERNAME}}                    环境变量，未设置时在控制台询问
+//   {{clipboard}}                       当前剪贴板中的文本
+//   {{prompt:主机名}}                   在控制台询问，同一名称只问一次
+//   {{uuid}}                            随机 UUID
+// 变量名必须紧跟 {{，因此 {{ date }} 这样的 Jinja/Handlebars 写法原样保留。
+// \{{ 表示字面的 {{，其他反斜杠和无法识别的 {{...}} 原样保留。
+// console 为 false 时不能读取标准输入，需要询问的变量返回错误
+pub fn expand(
+    text: &str,
+    clipboard: &ClipboardManager,
+    console: bool,
+) -> Result<Zeroizing<String>, String> {
+    if !text.contains("{{") {
+        return Ok(Zeroizing::new(text.to_string()));
+    }
+
+    let mut out = Zeroizing::new(String::with_capacity(text.len()));
+    let mut answers: HashMap<String, Zeroizing<String>> = HashMap::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        // 转义的 \{{
+        if rest[..start].ends_with('\\') {
+            out.push_str(&rest[..start - 1]);
+            out.push_str("{{");
+            rest = &rest[start + 2..];
+            continue;
+        }
+
+        out.push_str(&rest[..start]);
+        let end = match rest[start + 2..].find("}}") {
+            Some(end) => start + 2 + end,
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        };
+
+        let placeholder = &rest[start + 2..end];
+        match resolve(placeholder, clipboard, console, &mut answers)? {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[start..end + 2]),
+        }
+        rest = &rest[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+// 返回 None 表示不是模板变量，应原样输出
+fn resolve(
+    placeholder: &str,
+    clipboard: &ClipboardManager,
+    console: bool,
+    answers: &mut HashMap<String, Zeroizing<String>>,
+) -> Result<Option<Zeroizing<String>>, String> {
+    let (name, arg) = match placeholder.split_once(':') {
+        Some((name, arg)) => (name, Some(arg)),
+        None => (placeholder, None),
+    };
+
+    let value = match (name, arg) {
+        ("date", format) => {
+            match format_date(&local_time(), format.unwrap_or(DEFAULT_DATE_FORMAT)) {
+                Ok(date) => date,
+                Err(e) => {
+                    warn!("模板变量无法展开，原样输入: {}", e);
+                    return Ok(None);
+                }
+            }
+        }
+        ("uuid", None) => new_uuid()?,
+        ("clipboard", None) => clipboard.peek_clipboard()?.to_string(),
+        ("env", Some(var)) => match std::env::var(var.trim()) {
+            Ok(value) => value,
+            Err(_) => {
+                let label = format!("环境变量 {} 未设置，请输入 (Environment variable {} is not set, enter a value)", var.trim(), var.trim());
+                return ask(&label, console, answers).map(Some);
+            }
+        },
+        ("prompt", Some(label)) => return ask(label.trim(), console, answers).map(Some),
+        _ => return Ok(None),
+    };
+    Ok(Some(Zeroizing::new(value)))
+}
+
+// 在控制台询问，同一问题只问一次
+fn ask(
+    label: &str,
+    console: bool,
+    answers: &mut HashMap<String, Zeroizing<String>>,
+) -> Result<Zeroizing<String>, String> {
+    if let Some(answer) = answers.get(label) {
+        return Ok(answer.clone());
+    }
+    if !console {
+        return Err(format!(
+            "模板变量需要在控制台输入，请从菜单输入 (A template variable asks for input, type it from the menu): {}",
+            label
+        ));
+    }
+
+    print!("{}: ", label);
+    io::stdout().flush().map_err(|e| e.to_string())?;
+    let mut input = Zeroizing::new(String::new());
+    let read = io::stdin()
+        .read_line(&mut input)
+        .map_err(|e| format!("无法读取输入: {}", e))?;
+    if read == 0 {
+        return Err(format!("模板变量需要输入，但标准输入已结束: {}", label));
+    }
+    let answer = Zeroizing::new(input.trim_end_matches(['\r', '\n']).to_string());
+    answers.insert(label.to_string(), answer.clone());
+    Ok(answer)
+}
+
+fn local_time() -> SYSTEMTIME {
+    unsafe {
+        let mut time: SYSTEMTIME = std::mem::zeroed();
+        GetLocalTime(&mut time);
+        time
+    }
+}
+
+// 支持 %Y %y %m %d %H %M %S %%
+fn format_date(time: &SYSTEMTIME, format: &str) -> Result<String, String> {
+    let mut out = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('Y') => out.push_str(&format!("{:04}", time.wYear)),
+            Some('y') => out.push_str(&format!("{:02}", time.wYear % 100)),
+            Some('m') => out.push_str(&format!("{:02}", time.wMonth)),
+            Some('d') => out.push_str(&format!("{:02}", time.wDay)),
+            Some('H') => out.push_str(&format!("{:02}", time.wHour)),
+            Some('M') => out.push_str(&format!("{:02}", time.wMinute)),
+            Some('S') => out.push_str(&format!("{:02}", time.wSecond)),
+            Some('%') => out.push('%'),
+            Some(other) => return Err(format!("不支持的日期格式: %{}", other)),
+            None => return Err("日期格式以 % 结尾".into()),
+        }
+    }
+    Ok(out)
+}
+
+fn new_uuid() -> Result<String, String> {
+    let mut uuid: GUID = unsafe { std::mem::zeroed() };
+    let result = unsafe { CoCreateGuid(&mut uuid) };
+    if !SUCCEEDED(result) {
+        return Err(format!("无法生成UUID，错误码: {:#x}", result));
+    }
+
+    let d = uuid.Data4;
+    Ok(format!(
+        "{:08x}-{:04x}-{:04x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+        uuid.Data1, uuid.Data2, uuid.Data3, d[0], d[1], d[2], d[3], d[4], d[5], d[6], d[7]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::ClipboardHistory;
+
+    fn expand_offline(text: &str) -> Result<String, String> {
+        let clipboard = ClipboardManager::with_history(ClipboardHistory::new(1));
+        expand(text, &clipboard, false).map(|text| text.to_string())
+    }
+
+    fn time() -> SYSTEMTIME {
+        let mut time: SYSTEMTIME = unsafe { std::mem::zeroed() };
+        time.wYear = 2024;
+        time.wMonth = 3;
+        time.wDay = 7;
+        time.wHour = 9;
+        time.wMinute = 5;
+        time.wSecond = 0;
+        time
+    }
+
+    #[test]
+    fn text_without_placeholders_is_unchanged() {
+        assert_eq!(expand_offline("a { b } c}}").unwrap(), "a { b } c}}");
+    }
+
+    #[test]
+    fn escaped_braces_keep_the_rest() {
+        assert_eq!(expand_offline(r"\{{date}}").unwrap(), "{{date}}");
+        assert_eq!(expand_offline(r"a\\{{x}} \b").unwrap(), r"a\{{x}} \b");
+    }
+
+    #[test]
+    fn unknown_and_spaced_names_are_kept() {
+        assert_eq!(expand_offline("{{ date }}").unwrap(), "{{ date }}");
+        assert_eq!(expand_offline("{{name}}").unwrap(), "{{name}}");
+        assert_eq!(
+            expand_offline("{{uuid:x}} {{clipboard:y}}").unwrap(),
+            "{{uuid:x}} {{clipboard:y}}"
+        );
+        assert_eq!(expand_offline("{{unclosed").unwrap(), "{{unclosed");
+        assert_eq!(expand_offline("{{}}").unwrap(), "{{}}");
+    }
+
+    #[test]
+    fn unsupported_date_format_is_kept() {
+        assert_eq!(expand_offline("{{date:%Q}}").unwrap(), "{{date:%Q}}");
+    }
+
+    #[test]
+    fn environment_variables() {
+        std::env::set_var("PASTE_BYPASS_TEMPLATE_TEST", "value");
+        assert_eq!(
+            expand_offline("[{{env:PASTE_BYPASS_TEMPLATE_TEST}}]").unwrap(),
+            "[value]"
+        );
+        // 未设置时需要询问，不能读取控制台时返回错误
+        assert!(expand_offline("{{env:PASTE_BYPASS_TEMPLATE_UNSET}}").is_err());
+        assert!(expand_offline("{{prompt:Host}}").is_err());
+    }
+
+    #[test]
+    fn date_formats() {
+        let time = time();
+        assert_eq!(
+            format_date(&time, DEFAULT_DATE_FORMAT).unwrap(),
+            "2024-03-07"
+        );
+        assert_eq!(
+            format_date(&time, "%y%m%d %H:%M:%S").unwrap(),
+            "240307 09:05:00"
+        );
+        assert_eq!(
+            format_date(&time, "100%% on %d/%m").unwrap(),
+            "100% on 07/03"
+        );
+        assert!(format_date(&time, "%H:%").is_err());
+        assert!(format_date(&time, "%A").is_err());
+    }
+}
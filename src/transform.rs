@@ -0,0 +1,200 @@
+// src/transform.rs
+use deunicode::deunicode_char;
+use serde::Deserialize;
+use unicode_normalization::UnicodeNormalization;
+use zeroize::Zeroizing;
+
+const DEFAULT_TAB_WIDTH: usize = 4;
+
+// 输入前对文本进行的转换，在配置方案中按顺序列出，例如
+// transforms = ["ascii_punctuation", "nfc", "strip_trailing_whitespace", "expand_tabs:4", "transliterate"]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Transform {
+    AsciiPunctuation,               // 弯引号、破折号、省略号和特殊空格替换为ASCII字符
+    Nfc,                            // Unicode NFC 规范化
+    Nfkc,                           // Unicode NFKC 规范化（全角字符等转为普通形式）
+    StripTrailingWhitespace,        // 去掉每行末尾的空白
+    ExpandTabs(usize),              // 制表符展开为空格，对齐到 N 列
+    Transliterate { pinyin: bool }, // 转写为 ASCII（é→e、ß→ss），pinyin 时汉字转为拼音，无法表示的字符替换为 '?'
+}
+
+impl TryFrom<String> for Transform {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, Self::Error> {
+        let (name, arg) = match spec.split_once(':') {
+            Some((name, arg)) => (name.trim(), Some(arg.trim())),
+            None => (spec.trim(), None),
+        };
+
+        let transform = match name {
+            "ascii_punctuation" | "smart_quotes" => Transform::AsciiPunctuation,
+            "nfc" => Transform::Nfc,
+            "nfkc" => Transform::Nfkc,
+            "strip_trailing_whitespace" => Transform::StripTrailingWhitespace,
+            "expand_tabs" => {
+                let width = match arg {
+                    Some(arg) => arg
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|&w| w > 0)
+                        .ok_or_else(|| format!("制表符宽度无效: {}", arg))?,
+                    None => DEFAULT_TAB_WIDTH,
+                };
+                return Ok(Transform::ExpandTabs(width));
+            }
+            "transliterate" => {
+                return match arg {
+                    None => Ok(Transform::Transliterate { pinyin: false }),
+                    Some("pinyin") => Ok(Transform::Transliterate { pinyin: true }),
+                    Some(arg) => Err(format!("未知的转写选项: {}", arg)),
+                };
+            }
+            _ => return Err(format!("未知的文本转换: {}", spec)),
+        };
+
+        match arg {
+            Some(_) => Err(format!("文本转换 {} 不接受参数", name)),
+            None => Ok(transform),
+        }
+    }
+}
+
+impl Transform {
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            Transform::AsciiPunctuation => ascii_punctuation(text),
+            Transform::Nfc => text.nfc().collect(),
+            Transform::Nfkc => text.nfkc().collect(),
+            Transform::StripTrailingWhitespace => strip_trailing_whitespace(text),
+            Transform::ExpandTabs(width) => expand_tabs(text, *width),
+            Transform::Transliterate { pinyin } => transliterate(text, *pinyin, &mut Vec::new()),
+        }
+    }
+}
+
+// 依次执行所有转换，中间结果在丢弃时清零
+pub fn apply_all(transforms: &[Transform], text: &str) -> Zeroizing<String> {
+    transforms
+        .iter()
+        .fold(Zeroizing::new(text.to_string()), |text, transform| {
+            Zeroizing::new(transform.apply(&text))
+        })
+}
+
+// 依次执行转换，返回转写时无法用 ASCII 表示的字符（去重，按出现顺序）
+pub fn unrepresentable(transforms: &[Transform], text: &str) -> Vec<char> {
+    let mut lost = Vec::new();
+    let mut text = Zeroizing::new(text.to_string());
+    for transform in transforms {
+        text = Zeroizing::new(match transform {
+            Transform::Transliterate { pinyin } => transliterate(&text, *pinyin, &mut lost),
+            _ => transform.apply(&text),
+        });
+    }
+    lost
+}
+
+fn ascii_punctuation(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '‘' | '’' | '‚' | '‛' | '′' => out.push('\''),
+            '“' | '”' | '„' | '‟' | '″' => out.push('"'),
+            '‐' | '‑' | '‒' | '–' | '—' | '―' | '−' => out.push('-'),
+            '…' => out.push_str("..."),
+            '\u{a0}' | '\u{2002}'..='\u{200a}' | '\u{202f}' | '\u{3000}' => out.push(' '),
+            '\u{200b}' | '\u{feff}' => {} // 零宽字符
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn strip_trailing_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let (content, ending) = match line.strip_suffix("\r\n") {
+            Some(content) => (content, "\r\n"),
+            None => match line.strip_suffix('\n') {
+                Some(content) => (content, "\n"),
+                None => (line, ""),
+            },
+        };
+        out.push_str(content.trim_end());
+        out.push_str(ending);
+    }
+    out
+}
+
+// 按列对齐展开制表符
+fn expand_tabs(text: &str, width: usize) -> String {
+    let width = width.max(1);
+    let mut out = String::with_capacity(text.len());
+    let mut column = 0;
+    for c in text.chars() {
+        match c {
+            '\t' => {
+                let spaces = width - column % width;
+                out.extend(std::iter::repeat_n(' ', spaces));
+                column += spaces;
+            }
+            '\n' | '\r' => {
+                out.push(c);
+                column = 0;
+            }
+            _ => {
+                out.push(c);
+                column += 1;
+            }
+        }
+    }
+    out
+}
+
+// 转写为 ASCII，无法表示的字符替换为 '?' 并记录到 lost
+fn transliterate(text: &str, pinyin: bool, lost: &mut Vec<char>) -> String {
+    let mut out = String::with_capacity(text.len());
+    // 汉字拼音末尾带空格，只有后面紧跟字母或数字时才保留
+    let mut pending_space = false;
+    for c in text.chars() {
+        if c.is_ascii() {
+            if pending_space && c.is_ascii_alphanumeric() {
+                out.push(' ');
+            }
+            pending_space = false;
+            out.push(c);
+            continue;
+        }
+
+        let piece = match deunicode_char(c) {
+            Some(piece) if pinyin || !is_han(c) => piece,
+            _ => {
+                if !lost.contains(&c) {
+                    lost.push(c);
+                }
+                "?"
+            }
+        };
+        if pending_space && piece.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+            out.push(' ');
+        }
+        pending_space = piece.len() > 1 && piece.ends_with(' ');
+        out.push_str(if pending_space {
+            piece.trim_end()
+        } else {
+            piece
+        });
+    }
+    out
+}
+
+// CJK 统一表意文字
+fn is_han(c: char) -> bool {
+    matches!(c,
+        '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{20000}'..='\u{2ffff}')
+}
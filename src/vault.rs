@@ -0,0 +1,512 @@
+// src/vault.rs
+use crate::config;
+use crate::input_sim::KeyStroke;
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use winapi::um::consoleapi::{GetConsoleMode, SetConsoleMode};
+use winapi::um::processenv::GetStdHandle;
+use winapi::um::winbase::STD_INPUT_HANDLE;
+use winapi::um::wincon::ENABLE_ECHO_INPUT;
+use winapi::um::winuser::{VK_RETURN, VK_SPACE, VK_TAB};
+use zeroize::Zeroizing;
+
+pub const VAULT_FILE_NAME: &str = "vault.toml";
+pub const DEFAULT_SEQUENCE: &str = "{USERNAME}{TAB}{PASSWORD}{ENTER}";
+
+const VAULT_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+
+// Argon2id 参数：64 MiB 内存，3 次迭代
+const M_COST: u32 = 64 * 1024;
+const T_COST: u32 = 3;
+const P_COST: u32 = 1;
+
+// 文件中参数的上限，防止被篡改的文件在解锁时占用大量内存和时间
+const MAX_M_COST: u32 = 256 * 1024; // 256 MiB
+const MAX_T_COST: u32 = 16;
+const MAX_P_COST: u32 = 16;
+
+// 条目的字段，例如 username、password，可选的 sequence 为自动输入序列
+pub type VaultEntry = BTreeMap<String, Zeroizing<String>>;
+
+// 加密前的内容
+#[derive(Default, Serialize, Deserialize)]
+struct VaultData {
+    #[serde(default)]
+    entry: BTreeMap<String, VaultEntry>,
+}
+
+// 密码库文件，密钥由口令经 Argon2id 派生，内容用 ChaCha20-Poly1305 加密
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    salt: String,  // 十六进制
+    nonce: String, // 十六进制，每次保存重新生成
+    data: String,  // 十六进制密文
+}
+
+// 已解锁的密码库，丢弃时密钥和内容被清零
+pub struct Vault {
+    path: PathBuf,
+    salt: Vec<u8>,
+    params: (u32, u32, u32),
+    key: Zeroizing<[u8; 32]>,
+    data: VaultData,
+}
+
+impl Vault {
+    // 用新口令创建空的密码库
+    pub fn create(path: PathBuf, passphrase: &str) -> Result<Self, String> {
+        Vault::create_with_params(path, passphrase, (M_COST, T_COST, P_COST))
+    }
+
+    fn create_with_params(
+        path: PathBuf,
+        passphrase: &str,
+        params: (u32, u32, u32),
+    ) -> Result<Self, String> {
+        if path.exists() {
+            return Err(format!("密码库已存在: {}", path.display()));
+        }
+
+        let mut salt = vec![0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let vault = Vault {
+            key: derive_key(passphrase, &salt, params)?,
+            path,
+            salt,
+            params,
+            data: VaultData::default(),
+        };
+        vault.save()?;
+        info!("已创建密码库: {}", vault.path.display());
+        Ok(vault)
+    }
+
+    // 用口令解锁密码库
+    pub fn open(path: PathBuf, passphrase: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(&path).map_err(|e| {
+            format!(
+                "无法读取密码库 {}: {}，可使用 paste_bypass vault init 创建",
+                path.display(),
+                e
+            )
+        })?;
+        let file: VaultFile =
+            toml::from_str(&text).map_err(|e| format!("密码库格式错误: {}", e))?;
+        if file.version != VAULT_VERSION {
+            return Err(format!("不支持的密码库版本: {}", file.version));
+        }
+
+        let salt = from_hex(&file.salt)?;
+        let nonce = from_hex(&file.nonce)?;
+        if nonce.len() != 12 {
+            return Err("密码库格式错误: nonce 长度无效".into());
+        }
+        let params = (file.m_cost, file.t_cost, file.p_cost);
+        if file.m_cost > MAX_M_COST || file.t_cost > MAX_T_COST || file.p_cost > MAX_P_COST {
+            return Err(format!(
+                "密码库参数超出范围: m={} t={} p={}",
+                file.m_cost, file.t_cost, file.p_cost
+            ));
+        }
+        let key = derive_key(passphrase, &salt, params)?;
+
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&*key));
+        let plaintext = Zeroizing::new(
+            cipher
+                .decrypt(
+                    Nonce::from_slice(&nonce),
+                    Payload {
+                        msg: &from_hex(&file.data)?,
+                        aad: header(params).as_bytes(),
+                    },
+                )
+                .map_err(|_| "口令错误或密码库已损坏".to_string())?,
+        );
+        let plaintext =
+            std::str::from_utf8(&plaintext).map_err(|_| "密码库内容无效".to_string())?;
+        let data: VaultData =
+            toml::from_str(plaintext).map_err(|e| format!("密码库内容无效: {}", e))?;
+
+        info!("已解锁密码库，共 {} 个条目", data.entry.len());
+        Ok(Vault {
+            path,
+            salt,
+            params,
+            key,
+            data,
+        })
+    }
+
+    // 按序列解析条目的按键；未指定序列时使用条目的 sequence 字段或默认序列
+    pub fn strokes(
+        &self,
+        name: &str,
+        sequence: Option<&str>,
+    ) -> Result<Zeroizing<Vec<KeyStroke>>, String> {
+        let entry = self
+            .data
+            .entry
+            .get(name)
+            .ok_or_else(|| format!("条目不存在: {}", name))?;
+        let sequence = sequence
+            .or(entry.get("sequence").map(|s| s.as_str()))
+            .unwrap_or(DEFAULT_SEQUENCE);
+        sequence_strokes(entry, sequence)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.entry.is_empty()
+    }
+
+    // 添加或替换条目并保存，返回是否替换了已有条目
+    pub fn set(&mut self, name: &str, entry: VaultEntry) -> Result<bool, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("条目名称不能为空".into());
+        }
+        let replaced = self.data.entry.insert(name.to_string(), entry).is_some();
+        self.save()?;
+        Ok(replaced)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<(), String> {
+        if self.data.entry.remove(name).is_none() {
+            return Err(format!("条目不存在: {}", name));
+        }
+        self.save()
+    }
+
+    // 打印条目名称和字段名，不显示字段的值
+    pub fn print(&self) {
+        if self.data.entry.is_empty() {
+            println!("密码库为空 (Vault is empty)");
+            return;
+        }
+        for (name, entry) in &self.data.entry {
+            let fields: Vec<&str> = entry.keys().map(String::as_str).collect();
+            println!("  {}: {}", name, fields.join(", "));
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let plaintext = Zeroizing::new(toml::to_string(&self.data).map_err(|e| e.to_string())?);
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&*self.key));
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let data = cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext.as_bytes(),
+                    aad: header(self.params).as_bytes(),
+                },
+            )
+            .map_err(|_| "加密失败".to_string())?;
+
+        let file = VaultFile {
+            version: VAULT_VERSION,
+            m_cost: self.params.0,
+            t_cost: self.params.1,
+            p_cost: self.params.2,
+            salt: to_hex(&self.salt),
+            nonce: to_hex(&nonce),
+            data: to_hex(&data),
+        };
+        let text = toml::to_string(&file).map_err(|e| e.to_string())?;
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("无法创建目录 {}: {}", dir.display(), e))?;
+        }
+        config::write_atomic(&self.path, text.as_bytes())
+            .map_err(|e| format!("无法保存密码库 {}: {}", self.path.display(), e))
+    }
+}
+
+// 密码库保存在配置文件旁
+pub fn vault_path(config_path: Option<&Path>) -> Result<PathBuf, String> {
+    config_path
+        .and_then(Path::parent)
+        .map(|dir| dir.join(VAULT_FILE_NAME))
+        .ok_or_else(|| "无法确定密码库位置，请使用 --config 指定配置文件".into())
+}
+
+// 将自动输入序列解析为按键，例如 {USERNAME}{TAB}{PASSWORD}{ENTER}
+// {TAB} {ENTER} {SPACE} 为按键，其他 {名称} 为条目字段（不区分大小写），{{} 和 {}} 为字面的花括号
+// 字段内容直接按字符输入，不经过剪贴板和文本转换
+fn sequence_strokes(
+    entry: &VaultEntry,
+    sequence: &str,
+) -> Result<Zeroizing<Vec<KeyStroke>>, String> {
+    let mut strokes = Zeroizing::new(Vec::new());
+    let mut rest = sequence;
+
+    while let Some(start) = rest.find('{') {
+        strokes.extend(rest[..start].chars().map(KeyStroke::Char));
+        rest = &rest[start..];
+
+        // {{} 和 {}}
+        if let Some(after) = rest.strip_prefix("{{}") {
+            strokes.push(KeyStroke::Char('{'));
+            rest = after;
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix("{}}") {
+            strokes.push(KeyStroke::Char('}'));
+            rest = after;
+            continue;
+        }
+
+        let end = rest
+            .find('}')
+            .ok_or_else(|| format!("自动输入序列缺少 }}: {}", rest))?;
+        let token = &rest[1..end];
+        match token.to_ascii_uppercase().as_str() {
+            "TAB" => strokes.push(KeyStroke::Key(VK_TAB as u16, 0)),
+            "ENTER" => strokes.push(KeyStroke::Key(VK_RETURN as u16, 0)),
+            "SPACE" => strokes.push(KeyStroke::Key(VK_SPACE as u16, 0)),
+            _ => {
+                let value = entry
+                    .iter()
+                    .find(|(field, _)| field.eq_ignore_ascii_case(token))
+                    .map(|(_, value)| value)
+                    .ok_or_else(|| format!("条目中没有字段: {}", token))?;
+                strokes.extend(value.chars().map(KeyStroke::Char));
+            }
+        }
+        rest = &rest[end + 1..];
+    }
+    strokes.extend(rest.chars().map(KeyStroke::Char));
+    Ok(strokes)
+}
+
+// 在控制台读取一行，hidden 时不回显
+pub fn read_line(prompt: &str, hidden: bool) -> Result<Zeroizing<String>, String> {
+    print!("{}: ", prompt);
+    io::stdout().flush().map_err(|e| e.to_string())?;
+
+    // 关闭控制台回显，标准输入不是控制台时照常读取
+    let handle = unsafe { GetStdHandle(STD_INPUT_HANDLE) };
+    let mut mode = 0;
+    let restore = hidden && unsafe { GetConsoleMode(handle, &mut mode) } != 0;
+    if restore {
+        unsafe { SetConsoleMode(handle, mode & !ENABLE_ECHO_INPUT) };
+    }
+
+    let mut input = Zeroizing::new(String::new());
+    let result = io::stdin().read_line(&mut input);
+
+    if restore {
+        unsafe { SetConsoleMode(handle, mode) };
+        println!();
+    }
+    match result {
+        Ok(0) => Err("输入已结束".into()),
+        Ok(_) => Ok(Zeroizing::new(
+            input.trim_end_matches(['\r', '\n']).to_string(),
+        )),
+        Err(e) => Err(format!("无法读取输入: {}", e)),
+    }
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    (m_cost, t_cost, p_cost): (u32, u32, u32),
+) -> Result<Zeroizing<[u8; 32]>, String> {
+    let params = Params::new(m_cost, t_cost, p_cost, Some(32))
+        .map_err(|e| format!("密码库参数无效: {}", e))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut *key)
+        .map_err(|e| format!("无法派生密钥: {}", e))?;
+    Ok(key)
+}
+
+// 附加认证数据，防止文件头中的参数被篡改
+fn header((m_cost, t_cost, p_cost): (u32, u32, u32)) -> String {
+    format!(
+        "paste_bypass vault v{} m={} t={} p={}",
+        VAULT_VERSION, m_cost, t_cost, p_cost
+    )
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(text: &str) -> Result<Vec<u8>, String> {
+    if !text.is_ascii() || !text.len().is_multiple_of(2) {
+        return Err("密码库格式错误: 十六进制长度无效".into());
+    }
+    // from_str_radix 接受前导的 + 号，需单独检查
+    if !text.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err("密码库格式错误: 十六进制内容无效".into());
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&text[i..i + 2], 16)
+                .map_err(|_| "密码库格式错误: 十六进制内容无效".to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试使用较小的参数，避免每次派生密钥占用 64 MiB
+    const TEST_PARAMS: (u32, u32, u32) = (64, 1, 1);
+
+    // 每个测试使用单独的临时目录
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "paste_bypass_vault_{}_{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join(VAULT_FILE_NAME)
+    }
+
+    fn entry(fields: &[(&str, &str)]) -> VaultEntry {
+        fields
+            .iter()
+            .map(|&(name, value)| (name.to_string(), Zeroizing::new(value.to_string())))
+            .collect()
+    }
+
+    fn chars(text: &str) -> Vec<KeyStroke> {
+        text.chars().map(KeyStroke::Char).collect()
+    }
+
+    // 创建含一个条目的密码库，返回文件路径
+    fn sample(name: &str) -> PathBuf {
+        let path = temp_path(name);
+        let mut vault = Vault::create_with_params(path.clone(), "correct horse", TEST_PARAMS)
+            .expect("创建密码库");
+        vault
+            .set("mail", entry(&[("username", "me"), ("password", "p@ss")]))
+            .expect("保存条目");
+        path
+    }
+
+    fn rewrite(path: &Path, edit: impl FnOnce(&mut VaultFile)) {
+        let mut file: VaultFile = toml::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        edit(&mut file);
+        std::fs::write(path, toml::to_string(&file).unwrap()).unwrap();
+    }
+
+    fn open_error(path: &Path, passphrase: &str) -> String {
+        match Vault::open(path.to_path_buf(), passphrase) {
+            Ok(_) => panic!("密码库不应能打开"),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let path = sample("round_trip");
+        let vault = Vault::open(path.clone(), "correct horse").unwrap();
+        let strokes = vault
+            .strokes("mail", Some("{USERNAME}:{PASSWORD}"))
+            .unwrap();
+        assert_eq!(*strokes, chars("me:p@ss"));
+        assert!(Vault::create(path, "other").is_err());
+    }
+
+    #[test]
+    fn wrong_passphrase() {
+        let path = sample("wrong_passphrase");
+        assert!(open_error(&path, "Correct horse").contains("口令错误"));
+    }
+
+    #[test]
+    fn corrupted_or_tampered_file() {
+        let path = sample("corrupted");
+        rewrite(&path, |file| {
+            let flipped = if file.data.starts_with('0') { "1" } else { "0" };
+            file.data.replace_range(..1, flipped);
+        });
+        assert!(open_error(&path, "correct horse").contains("已损坏"));
+
+        // 文件头参与认证，修改参数后无法解密
+        let path = sample("tampered");
+        rewrite(&path, |file| file.t_cost += 1);
+        assert!(open_error(&path, "correct horse").contains("已损坏"));
+
+        let path = sample("nonce");
+        rewrite(&path, |file| file.nonce.truncate(8));
+        assert!(open_error(&path, "correct horse").contains("nonce"));
+    }
+
+    #[test]
+    fn truncated_file() {
+        let path = sample("truncated");
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, &text[..text.len() / 2]).unwrap();
+        assert!(open_error(&path, "correct horse").contains("格式错误"));
+    }
+
+    #[test]
+    fn excessive_parameters_are_rejected() {
+        let path = sample("limits");
+        rewrite(&path, |file| file.m_cost = MAX_M_COST + 1);
+        assert!(open_error(&path, "correct horse").contains("超出范围"));
+    }
+
+    #[test]
+    fn hex_decoding() {
+        assert_eq!(from_hex("00ff7A"), Ok(vec![0x00, 0xff, 0x7a]));
+        assert_eq!(from_hex(&to_hex(&[1, 2, 254])), Ok(vec![1, 2, 254]));
+        assert_eq!(from_hex(""), Ok(vec![]));
+        assert!(from_hex("abc").is_err());
+        assert!(from_hex("zz").is_err());
+        assert!(from_hex("+1").is_err());
+        assert!(from_hex("éé").is_err());
+    }
+
+    #[test]
+    fn default_sequence() {
+        let entry = entry(&[("username", "me"), ("password", "pw")]);
+        let tab = KeyStroke::Key(VK_TAB as u16, 0);
+        let enter = KeyStroke::Key(VK_RETURN as u16, 0);
+        let mut expected = chars("me");
+        expected.push(tab);
+        expected.extend(chars("pw"));
+        expected.push(enter);
+        assert_eq!(
+            *sequence_strokes(&entry, DEFAULT_SEQUENCE).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn sequence_literals_and_field_names() {
+        let entry = entry(&[("Pin", "42")]);
+        let space = KeyStroke::Key(VK_SPACE as u16, 0);
+        let mut expected = chars("{x}=");
+        expected.extend(chars("42"));
+        expected.push(space);
+        assert_eq!(
+            *sequence_strokes(&entry, "{{}x{}}={pin}{space}").unwrap(),
+            expected
+        );
+
+        assert!(sequence_strokes(&entry, "{PASSWORD}")
+            .unwrap_err()
+            .contains("PASSWORD"));
+        assert!(sequence_strokes(&entry, "{PIN").is_err());
+    }
+}
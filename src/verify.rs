@@ -0,0 +1,323 @@
+// src/verify.rs
+// 输入后校验：选中并复制目标输入框的内容，与实际发送的字符比较
+use crate::hotkey::{self, parse_hotkey};
+use crate::input_sim::{KeyStroke, MOD_ALT, MOD_CONTROL, MOD_SHIFT};
+use serde::Deserialize;
+use winapi::um::winuser::{VK_BACK, VK_DELETE, VK_LEFT, VK_RETURN, VK_TAB};
+use zeroize::Zeroizing;
+
+// 重新输入前删除不一致内容的方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteMode {
+    #[default]
+    Backspace, // 光标在末尾，按退格键删除不一致处之后的字符
+    SelectToEnd, // 从末尾按 Shift+Left 选中到不一致处，一次删除
+}
+
+// 第一个不一致的位置
+pub struct Divergence {
+    pub offset: usize, // 字符序号，从0开始
+    pub line: usize,   // 从1开始
+    pub column: usize, // 从1开始
+    pub expected: Option<char>,
+    pub actual: Option<char>,
+    pub expected_len: usize,
+    pub actual_len: usize,
+}
+
+impl Divergence {
+    // 生成可打印的说明，敏感内容模式下不显示字符
+    pub fn describe(&self, secret: bool) -> String {
+        format!(
+            "第 {line} 行第 {column} 列（第 {n} 个字符）应为 {expected}，实际为 {actual}；\
+             发送 {sent} 个字符，读回 {read} 个字符 \
+             (line {line}, column {column}, character {n}: expected {expected_en}, found {actual_en}; \
+             {sent} characters sent, {read} read back)",
+            line = self.line,
+            column = self.column,
+            n = self.offset + 1,
+            expected = show(self.expected, secret, "结尾"),
+            actual = show(self.actual, secret, "结尾"),
+            expected_en = show(self.expected, secret, "end of text"),
+            actual_en = show(self.actual, secret, "end of text"),
+            sent = self.expected_len,
+            read = self.actual_len,
+        )
+    }
+}
+
+// 解析按键序列，例如 ["Home", "Shift+End"]
+pub fn key_strokes(keys: &[String]) -> Result<Vec<KeyStroke>, String> {
+    keys.iter()
+        .map(|key| {
+            let (modifiers, vk) = parse_hotkey(key)?;
+            if modifiers & hotkey::MOD_WIN != 0 {
+                return Err(format!("校验按键不支持 Win 键: {}", key));
+            }
+            let mut flags = 0;
+            if modifiers & hotkey::MOD_CONTROL != 0 {
+                flags |= MOD_CONTROL;
+            }
+            if modifiers & hotkey::MOD_SHIFT != 0 {
+                flags |= MOD_SHIFT;
+            }
+            if modifiers & hotkey::MOD_ALT != 0 {
+                flags |= MOD_ALT;
+            }
+            Ok(KeyStroke::Key(vk as u16, flags))
+        })
+        .collect()
+}
+
+// 按键序列在输入框中应产生的文本：回车为换行，Tab 键为制表符，其他按键不产生字符
+pub fn sent_text(strokes: &[KeyStroke]) -> Zeroizing<String> {
+    Zeroizing::new(strokes.iter().filter_map(produced).collect())
+}
+
+fn produced(stroke: &KeyStroke) -> Option<char> {
+    match *stroke {
+        KeyStroke::Char(c) | KeyStroke::Unicode(c) => Some(c),
+        KeyStroke::Key(vk, _) if vk == VK_RETURN as u16 => Some('\n'),
+        KeyStroke::Key(vk, 0) if vk == VK_TAB as u16 => Some('\t'),
+        KeyStroke::Key(..) => None,
+    }
+}
+
+// 从第 offset 个字符继续输入时需要发送的按键。
+// 前一个字符之后不产生字符的按键（例如替换自动缩进的 Shift+Home）属于这个字符，一并保留；
+// 紧跟在前一个字符后的 Delete 是删除编辑器自动补全的闭合字符，属于前一个字符，不再发送
+pub fn resume_from(strokes: &[KeyStroke], offset: usize) -> &[KeyStroke] {
+    if offset == 0 {
+        return strokes;
+    }
+    let mut count = 0;
+    for (i, stroke) in strokes.iter().enumerate() {
+        if produced(stroke).is_some() {
+            count += 1;
+            if count == offset {
+                let rest = &strokes[i + 1..];
+                let closers = rest
+                    .iter()
+                    .take_while(|&&stroke| stroke == KeyStroke::Key(VK_DELETE as u16, 0))
+                    .count();
+                return &rest[closers..];
+            }
+        }
+    }
+    &[]
+}
+
+// 删除不一致处之后内容的按键，调用前光标应在输入框末尾（取消选中的按键默认为 Ctrl+End）。
+// 两种方式都从末尾倒数，不依赖 Home 键的行为和自动换行
+pub fn delete_tail(divergence: &Divergence, mode: DeleteMode) -> Vec<KeyStroke> {
+    let extra = divergence.actual_len.saturating_sub(divergence.offset);
+    if extra == 0 {
+        return Vec::new();
+    }
+    let key = |vk: i32, flags: u8| KeyStroke::Key(vk as u16, flags);
+    match mode {
+        DeleteMode::Backspace => vec![key(VK_BACK, 0); extra],
+        DeleteMode::SelectToEnd => {
+            let mut strokes = vec![key(VK_LEFT, MOD_SHIFT); extra];
+            strokes.push(key(VK_DELETE, 0));
+            strokes
+        }
+    }
+}
+
+// 比较发送的文本和读回的文本，换行统一为 \n
+pub fn first_divergence(expected: &str, actual: &str) -> Option<Divergence> {
+    let expected: Zeroizing<Vec<char>> = Zeroizing::new(normalize_newlines(expected).collect());
+    let actual: Zeroizing<Vec<char>> = Zeroizing::new(normalize_newlines(actual).collect());
+
+    let offset = expected
+        .iter()
+        .zip(actual.iter())
+        .position(|(a, b)| a != b)
+        .unwrap_or(expected.len().min(actual.len()));
+    if offset == expected.len() && offset == actual.len() {
+        return None;
+    }
+
+    let before = &expected[..offset];
+    let line = before.iter().filter(|&&c| c == '\n').count() + 1;
+    let column = offset - before.iter().rposition(|&c| c == '\n').map_or(0, |i| i + 1) + 1;
+    Some(Divergence {
+        offset,
+        line,
+        column,
+        expected: expected.get(offset).copied(),
+        actual: actual.get(offset).copied(),
+        expected_len: expected.len(),
+        actual_len: actual.len(),
+    })
+}
+
+fn normalize_newlines(text: &str) -> impl Iterator<Item = char> + '_ {
+    let mut chars = text.chars().peekable();
+    std::iter::from_fn(move || {
+        let c = chars.next()?;
+        if c == '\r' {
+            if chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            return Some('\n');
+        }
+        Some(c)
+    })
+}
+
+// 控制字符和空白显示为码位，文本结束处显示为 end
+fn show(c: Option<char>, secret: bool, end: &str) -> String {
+    match c {
+        None => end.into(),
+        Some(_) if secret => "*".into(),
+        Some(c) if c.is_control() || c.is_whitespace() => format!("U+{:04X}", c as u32),
+        Some(c) => format!("'{}'", c),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winapi::um::winuser::VK_HOME;
+
+    fn key(vk: i32, flags: u8) -> KeyStroke {
+        KeyStroke::Key(vk as u16, flags)
+    }
+
+    fn divergence(offset: usize, actual_len: usize) -> Divergence {
+        Divergence {
+            offset,
+            line: 1,
+            column: offset + 1,
+            expected: None,
+            actual: None,
+            expected_len: offset,
+            actual_len,
+        }
+    }
+
+    #[test]
+    fn identical_text_has_no_divergence() {
+        assert!(first_divergence("abc\ndef", "abc\ndef").is_none());
+        // 只有换行方式不同时视为一致
+        assert!(first_divergence("a\r\nb\rc", "a\nb\nc").is_none());
+    }
+
+    #[test]
+    fn line_and_column_count_crlf_once() {
+        let div = first_divergence("ab\r\ncd\r\nef", "ab\r\ncd\r\neX").unwrap();
+        assert_eq!((div.offset, div.line, div.column), (7, 3, 2));
+        assert_eq!((div.expected, div.actual), (Some('f'), Some('X')));
+        assert_eq!((div.expected_len, div.actual_len), (8, 8));
+    }
+
+    #[test]
+    fn divergence_at_line_start() {
+        let div = first_divergence("one\ntwo", "one\r\nTwo").unwrap();
+        assert_eq!((div.offset, div.line, div.column), (4, 2, 1));
+    }
+
+    #[test]
+    fn truncated_and_extra_text() {
+        let div = first_divergence("abcdef", "abc").unwrap();
+        assert_eq!((div.offset, div.expected, div.actual), (3, Some('d'), None));
+
+        let div = first_divergence("abc", "abc\n\n").unwrap();
+        assert_eq!(
+            (div.offset, div.expected, div.actual),
+            (3, None, Some('\n'))
+        );
+        assert_eq!(div.actual_len, 5);
+    }
+
+    #[test]
+    fn sent_text_counts_enter_and_tab() {
+        let strokes = [
+            KeyStroke::Char('a'),
+            key(VK_RETURN, 0),
+            key(VK_TAB, 0),
+            key(VK_TAB, MOD_SHIFT),
+            KeyStroke::Unicode('é'),
+        ];
+        assert_eq!(sent_text(&strokes).as_str(), "a\n\té");
+    }
+
+    #[test]
+    fn resume_keeps_keys_after_the_last_correct_character() {
+        let home = key(VK_HOME, MOD_SHIFT);
+        let strokes = [
+            KeyStroke::Char('a'),
+            key(VK_RETURN, 0),
+            home,
+            KeyStroke::Char('b'),
+            KeyStroke::Char('c'),
+        ];
+        assert_eq!(resume_from(&strokes, 0), &strokes[..]);
+        assert_eq!(resume_from(&strokes, 1), &strokes[1..]);
+        assert_eq!(
+            resume_from(&strokes, 2),
+            &[home, KeyStroke::Char('b'), KeyStroke::Char('c')]
+        );
+        assert_eq!(resume_from(&strokes, 4), &[] as &[KeyStroke]);
+        assert_eq!(resume_from(&strokes, 9), &[] as &[KeyStroke]);
+
+        // 自动补全的 { 之后的 Delete 属于 {，换行后的 Shift+Home 属于下一个字符
+        let enter = key(VK_RETURN, 0);
+        let strokes = [
+            KeyStroke::Char('{'),
+            key(VK_DELETE, 0),
+            enter,
+            home,
+            KeyStroke::Char('x'),
+        ];
+        assert_eq!(
+            resume_from(&strokes, 1),
+            &[enter, home, KeyStroke::Char('x')]
+        );
+        assert_eq!(resume_from(&strokes, 2), &[home, KeyStroke::Char('x')]);
+    }
+
+    #[test]
+    fn resume_drops_the_delete_of_an_auto_closed_pair() {
+        let delete = key(VK_DELETE, 0);
+        // f(x) 在自动补全括号的编辑器中：( 之后的 Delete 删除补全的 )
+        let strokes = [
+            KeyStroke::Char('f'),
+            KeyStroke::Char('('),
+            delete,
+            KeyStroke::Char('x'),
+            KeyStroke::Char(')'),
+        ];
+        assert_eq!(
+            resume_from(&strokes, 2),
+            &[KeyStroke::Char('x'), KeyStroke::Char(')')]
+        );
+        assert_eq!(resume_from(&strokes, 1), &strokes[1..]);
+    }
+
+    #[test]
+    fn delete_tail_counts_from_the_end() {
+        let div = divergence(3, 5);
+        assert_eq!(
+            delete_tail(&div, DeleteMode::Backspace),
+            vec![key(VK_BACK, 0); 2]
+        );
+        assert_eq!(
+            delete_tail(&div, DeleteMode::SelectToEnd),
+            vec![
+                key(VK_LEFT, MOD_SHIFT),
+                key(VK_LEFT, MOD_SHIFT),
+                key(VK_DELETE, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn delete_tail_is_empty_when_text_was_truncated() {
+        assert!(delete_tail(&divergence(3, 3), DeleteMode::Backspace).is_empty());
+        assert!(delete_tail(&divergence(4, 2), DeleteMode::SelectToEnd).is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+// src/watcher.rs
+// 剪贴板变化监听。只实现了 Windows（AddClipboardFormatListener），
+// 本程序的其余部分同样依赖 Windows API，因此没有 X11 后端。
+use log::{error, info};
+use std::ptr::null_mut;
+use std::sync::mpsc::channel;
+use std::thread;
+use winapi::shared::minwindef::{DWORD, LPARAM, LRESULT, UINT, WPARAM};
+use winapi::shared::windef::HWND;
+use winapi::um::libloaderapi::GetModuleHandleW;
+use winapi::um::processthreadsapi::GetCurrentThreadId;
+use winapi::um::winuser::{
+    AddClipboardFormatListener, CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW,
+    GetMessageW, PostThreadMessageW, RegisterClassW, RemoveClipboardFormatListener,
+    TranslateMessage, HWND_MESSAGE, MSG, WM_APP, WM_CLIPBOARDUPDATE, WM_QUIT, WNDCLASSW,
+};
+
+// 窗口过程收到剪贴板变化后转发给线程消息循环的消息
+const WM_APP_CLIPBOARD: UINT = WM_APP + 1;
+
+pub struct ClipboardWatcher {
+    thread_id: DWORD,
+    thread_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl ClipboardWatcher {
+    // 开始监听，每次剪贴板内容变化时在监听线程中调用回调
+    pub fn start<F>(callback: F) -> Result<Self, String>
+    where
+        F: Fn() + Send + 'static,
+    {
+        let (ready_tx, ready_rx) = channel();
+
+        let thread_handle = thread::spawn(move || unsafe {
+            let hwnd = match create_message_window() {
+                Ok(hwnd) => hwnd,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+
+            if AddClipboardFormatListener(hwnd) == 0 {
+                DestroyWindow(hwnd);
+                let _ = ready_tx.send(Err("无法监听剪贴板变化".to_string()));
+                return;
+            }
+
+            let _ = ready_tx.send(Ok(GetCurrentThreadId()));
+            info!("开始监听剪贴板变化");
+
+            // 消息循环，收到 WM_QUIT 时退出
+            let mut msg: MSG = std::mem::zeroed();
+            while GetMessageW(&mut msg, null_mut(), 0, 0) > 0 {
+                if msg.hwnd.is_null() && msg.message == WM_APP_CLIPBOARD {
+                    callback();
+                    continue;
+                }
+                TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+
+            RemoveClipboardFormatListener(hwnd);
+            DestroyWindow(hwnd);
+            info!("已停止监听剪贴板变化");
+        });
+
+        match ready_rx.recv() {
+            Ok(Ok(thread_id)) => Ok(ClipboardWatcher {
+                thread_id,
+                thread_handle: Some(thread_handle),
+            }),
+            Ok(Err(e)) => {
+                error!("{}", e);
+                let _ = thread_handle.join();
+                Err(e)
+            }
+            Err(_) => Err("剪贴板监听线程意外退出".into()),
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(handle) = self.thread_handle.take() {
+            unsafe { PostThreadMessageW(self.thread_id, WM_QUIT, 0, 0) };
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for ClipboardWatcher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+// WM_CLIPBOARDUPDATE 是发送消息，只能在窗口过程中收到，这里转发到线程队列
+unsafe extern "system" fn watcher_wndproc(
+    hwnd: HWND,
+    msg: UINT,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if msg == WM_CLIPBOARDUPDATE {
+        PostThreadMessageW(GetCurrentThreadId(), WM_APP_CLIPBOARD, 0, 0);
+        return 0;
+    }
+    DefWindowProcW(hwnd, msg, wparam, lparam)
+}
+
+// 创建仅接收消息的隐藏窗口
+unsafe fn create_message_window() -> Result<HWND, String> {
+    let class_name: Vec<u16> = "PasteBypassClipboardWatcher\0".encode_utf16().collect();
+    let instance = GetModuleHandleW(null_mut());
+
+    let mut class: WNDCLASSW = std::mem::zeroed();
+    class.lpfnWndProc = Some(watcher_wndproc);
+    class.hInstance = instance;
+    class.lpszClassName = class_name.as_ptr();
+    // 重复注册会失败，但已注册的窗口类仍可使用
+    RegisterClassW(&class);
+
+    let hwnd = CreateWindowExW(
+        0,
+        class_name.as_ptr(),
+        class_name.as_ptr(),
+        0,
+        0,
+        0,
+        0,
+        0,
+        HWND_MESSAGE,
+        null_mut(),
+        instance,
+        null_mut(),
+    );
+    if hwnd.is_null() {
+        return Err("无法创建剪贴板监听窗口".into());
+    }
+    Ok(hwnd)
+}
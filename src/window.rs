@@ -0,0 +1,174 @@
+// src/window.rs
+use std::ffi::OsString;
+use std::os::windows::ffi::OsStringExt;
+use std::path::Path;
+use std::ptr;
+use winapi::shared::minwindef::{BOOL, DWORD, FALSE, LPARAM, TRUE};
+use winapi::shared::windef::HWND;
+use winapi::um::handleapi::CloseHandle;
+use winapi::um::processthreadsapi::{GetCurrentThreadId, OpenProcess};
+use winapi::um::winbase::QueryFullProcessImageNameW;
+use winapi::um::wincon::GetConsoleWindow;
+use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+use winapi::um::winuser::{
+    AttachThreadInput, BringWindowToTop, EnumWindows, GetAncestor, GetClassNameW,
+    GetForegroundWindow, GetGUIThreadInfo, GetWindowTextLengthW, GetWindowTextW,
+    GetWindowThreadProcessId, IsIconic, IsWindowVisible, SetForegroundWindow, ShowWindow, GA_ROOT,
+    GUITHREADINFO, SW_RESTORE,
+};
+
+// 窗口的标识信息，用于匹配配置方案
+#[derive(Debug, Clone)]
+pub struct WindowInfo {
+    pub title: String,
+    pub class: String,
+    pub exe: Option<String>, // 进程可执行文件名，例如 "notepad.exe"
+}
+
+impl WindowInfo {
+    // 获取当前前台窗口的信息
+    pub fn foreground() -> Option<WindowInfo> {
+        let hwnd = unsafe { GetForegroundWindow() };
+        WindowInfo::from_hwnd(hwnd)
+    }
+
+    pub fn from_hwnd(hwnd: HWND) -> Option<WindowInfo> {
+        if hwnd.is_null() {
+            return None;
+        }
+
+        let mut pid: DWORD = 0;
+        unsafe { GetWindowThreadProcessId(hwnd, &mut pid) };
+
+        Some(WindowInfo {
+            title: window_title(hwnd),
+            class: window_class(hwnd),
+            exe: process_exe_name(pid),
+        })
+    }
+}
+
+// 查找第一个匹配的可见顶层窗口，跳过本程序的控制台窗口
+pub fn find_window<F>(matches: F) -> Option<(HWND, WindowInfo)>
+where
+    F: Fn(&WindowInfo) -> bool,
+{
+    let console = unsafe { GetConsoleWindow() };
+    top_level_windows()
+        .into_iter()
+        .filter(|&hwnd| hwnd != console && unsafe { IsWindowVisible(hwnd) } != 0)
+        .filter_map(|hwnd| WindowInfo::from_hwnd(hwnd).map(|info| (hwnd, info)))
+        .find(|(_, info)| matches(info))
+}
+
+fn top_level_windows() -> Vec<HWND> {
+    unsafe extern "system" fn collect(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let windows = &mut *(lparam as *mut Vec<HWND>);
+        windows.push(hwnd);
+        TRUE
+    }
+
+    let mut windows: Vec<HWND> = Vec::new();
+    unsafe { EnumWindows(Some(collect), &mut windows as *mut Vec<HWND> as LPARAM) };
+    windows
+}
+
+// 子控件所在的顶层窗口
+pub fn root_window(hwnd: HWND) -> HWND {
+    unsafe { GetAncestor(hwnd, GA_ROOT) }
+}
+
+// 顶层窗口中拥有键盘焦点的控件，窗口不在前台时也会保留；没有时返回窗口本身
+pub fn focused_control(hwnd: HWND) -> HWND {
+    unsafe {
+        let thread = GetWindowThreadProcessId(hwnd, ptr::null_mut());
+        let mut info: GUITHREADINFO = std::mem::zeroed();
+        info.cbSize = std::mem::size_of::<GUITHREADINFO>() as DWORD;
+        if thread != 0
+            && GetGUIThreadInfo(thread, &mut info) != 0
+            && !info.hwndFocus.is_null()
+            && GetAncestor(info.hwndFocus, GA_ROOT) == hwnd
+        {
+            info.hwndFocus
+        } else {
+            hwnd
+        }
+    }
+}
+
+// 将窗口切换到前台，最小化的窗口先还原
+pub fn activate(hwnd: HWND) -> bool {
+    unsafe {
+        if IsIconic(hwnd) != 0 {
+            ShowWindow(hwnd, SW_RESTORE);
+        }
+        if SetForegroundWindow(hwnd) != 0 {
+            return true;
+        }
+
+        // 系统只允许前台线程切换前台窗口，临时挂接到当前前台窗口的输入队列后重试
+        let foreground = GetForegroundWindow();
+        let foreground_thread = GetWindowThreadProcessId(foreground, ptr::null_mut());
+        let current_thread = GetCurrentThreadId();
+        let attached = foreground_thread != 0
+            && foreground_thread != current_thread
+            && AttachThreadInput(current_thread, foreground_thread, TRUE) != 0;
+
+        BringWindowToTop(hwnd);
+        let ok = SetForegroundWindow(hwnd) != 0;
+
+        if attached {
+            AttachThreadInput(current_thread, foreground_thread, FALSE);
+        }
+        ok
+    }
+}
+
+pub fn window_title(hwnd: HWND) -> String {
+    unsafe {
+        let len = GetWindowTextLengthW(hwnd);
+        if len <= 0 {
+            return String::new();
+        }
+        let mut buf = vec![0u16; len as usize + 1];
+        let copied = GetWindowTextW(hwnd, buf.as_mut_ptr(), buf.len() as i32);
+        wide_to_string(&buf[..copied.max(0) as usize])
+    }
+}
+
+pub fn window_class(hwnd: HWND) -> String {
+    let mut buf = [0u16; 256];
+    let copied = unsafe { GetClassNameW(hwnd, buf.as_mut_ptr(), buf.len() as i32) };
+    wide_to_string(&buf[..copied.max(0) as usize])
+}
+
+// 根据进程ID获取可执行文件名（不含路径）
+pub fn process_exe_name(pid: DWORD) -> Option<String> {
+    if pid == 0 {
+        return None;
+    }
+
+    unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if process.is_null() {
+            return None;
+        }
+
+        let mut buf = [0u16; 1024];
+        let mut len = buf.len() as DWORD;
+        let ok = QueryFullProcessImageNameW(process, 0, buf.as_mut_ptr(), &mut len);
+        CloseHandle(process);
+        if ok == 0 {
+            return None;
+        }
+
+        let full_path = wide_to_string(&buf[..len as usize]);
+        Path::new(&full_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    }
+}
+
+fn wide_to_string(wide: &[u16]) -> String {
+    OsString::from_wide(wide).to_string_lossy().into_owned()
+}